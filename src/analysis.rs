@@ -0,0 +1,492 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+use spectrum_analyzer::scaling::SpectrumDataStats;
+use spectrum_analyzer::{FrequencyLimit, samples_fft_to_spectrum};
+
+use crate::binning::{BinAggregation, bin_spectrum};
+use crate::error::ConfigError;
+use crate::visualizer::{MAX_FFT_SIZE, MIN_WINDOW_SIZE, VisualizerConfig};
+use crate::window::WindowFunction;
+
+/// Largest supported number of constant-Q bins per octave.
+pub const MAX_BINS_PER_OCTAVE: u32 = 48;
+
+/// Largest supported constant-Q quality factor.
+pub const MAX_Q: f32 = 200.0;
+
+/// Bass window of multi-resolution analysis, also for presets that leave it
+/// out.
+fn default_bass_window() -> usize {
+    8192
+}
+
+/// Turns a window of samples into the power of each bin of the layout.
+pub trait SpectrumBackend: Send {
+    /// Power in each bin of `config.layout` for `samples`, the latest
+    /// `config.history_size()` samples.
+    fn analyze(&mut self, samples: &[f32], config: &VisualizerConfig) -> Vec<f32>;
+
+    /// The method this backend implements.
+    fn method(&self) -> AnalysisMethod;
+
+    /// Power a full-scale sine reads in the bin at its frequency, the 0 dBFS
+    /// reference.
+    fn full_scale_power(&self, config: &VisualizerConfig) -> f32;
+}
+
+/// How the spectrum of each window is computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum AnalysisMethod {
+    /// One FFT per window, binned onto the layout.
+    #[default]
+    Fft,
+    /// Constant-Q transform: one filter per bin whose length shrinks with
+    /// frequency, so bass is resolved while treble stays responsive.
+    /// Continuous scales get `bins_per_octave` bins per octave; each bin is
+    /// `1/q` of its center frequency wide.
+    ConstantQ { bins_per_octave: u32, q: f32 },
+    /// FFTs of `bass_window` samples for bass, `mid_window` samples for mids
+    /// and `treble_window` samples for treble, split at the crossovers in Hz.
+    /// The bass window is independent of the main window, which keeps
+    /// setting the hop and the level reference.
+    MultiResolution {
+        #[serde(default = "default_bass_window")]
+        bass_window: usize,
+        mid_window: usize,
+        treble_window: usize,
+        low_crossover: f32,
+        high_crossover: f32,
+    },
+}
+
+impl AnalysisMethod {
+    /// One of each kind, with 24 constant-Q bins per octave and
+    /// multi-resolution windows of 8192, 2048 and 512 samples split at
+    /// 250 Hz and 2 kHz.
+    pub fn options() -> [AnalysisMethod; 3] {
+        [
+            AnalysisMethod::Fft,
+            AnalysisMethod::constant_q(24),
+            AnalysisMethod::MultiResolution {
+                bass_window: default_bass_window(),
+                mid_window: 2048,
+                treble_window: 512,
+                low_crossover: 250.0,
+                high_crossover: 2000.0,
+            },
+        ]
+    }
+
+    /// Constant-Q with the Q factor at which adjacent bins just touch.
+    pub fn constant_q(bins_per_octave: u32) -> Self {
+        AnalysisMethod::ConstantQ {
+            bins_per_octave,
+            q: 1.0 / (2.0_f32.powf(1.0 / bins_per_octave.max(1) as f32) - 1.0),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AnalysisMethod::Fft => "FFT",
+            AnalysisMethod::ConstantQ { .. } => "Constant-Q",
+            AnalysisMethod::MultiResolution { .. } => "Multi-resolution",
+        }
+    }
+
+    /// True if both are the same method, ignoring parameters.
+    pub fn same_kind(&self, other: &AnalysisMethod) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match *self {
+            AnalysisMethod::Fft => Ok(()),
+            AnalysisMethod::ConstantQ { bins_per_octave, q } => {
+                if (1..=MAX_BINS_PER_OCTAVE).contains(&bins_per_octave)
+                    && (1.0..=MAX_Q).contains(&q)
+                {
+                    Ok(())
+                } else {
+                    Err(ConfigError::ConstantQ { bins_per_octave, q })
+                }
+            }
+            AnalysisMethod::MultiResolution {
+                bass_window,
+                mid_window,
+                treble_window,
+                low_crossover,
+                high_crossover,
+            } => {
+                let windows = MIN_WINDOW_SIZE <= treble_window
+                    && treble_window <= mid_window
+                    && mid_window <= bass_window
+                    && bass_window <= MAX_FFT_SIZE;
+                let crossovers = low_crossover > 0.0 && high_crossover > low_crossover;
+                if windows && crossovers && high_crossover.is_finite() {
+                    Ok(())
+                } else {
+                    Err(ConfigError::MultiResolution {
+                        bass_window,
+                        mid_window,
+                        treble_window,
+                        low_crossover,
+                        high_crossover,
+                    })
+                }
+            }
+        }
+    }
+
+    /// A new backend for this method.
+    pub fn backend(&self) -> Box<dyn SpectrumBackend> {
+        match *self {
+            AnalysisMethod::Fft => Box::new(FftBackend),
+            AnalysisMethod::ConstantQ { bins_per_octave, q } => {
+                Box::new(ConstantQBackend::new(bins_per_octave, q))
+            }
+            AnalysisMethod::MultiResolution {
+                bass_window,
+                mid_window,
+                treble_window,
+                low_crossover,
+                high_crossover,
+            } => Box::new(MultiResolutionBackend::new(
+                bass_window,
+                mid_window,
+                treble_window,
+                low_crossover,
+                high_crossover,
+            )),
+        }
+    }
+}
+
+/// The FFT of each window, with its lines binned onto the layout.
+pub struct FftBackend;
+
+impl SpectrumBackend for FftBackend {
+    fn analyze(&mut self, samples: &[f32], config: &VisualizerConfig) -> Vec<f32> {
+        let window_size = config.window_size();
+        let line_spacing = config.sample_rate as f32 / config.fft_size() as f32;
+        // Scale by the window length rather than the FFT size, so padding
+        // does not change the level
+        let lines = fft_lines(
+            &samples[..window_size],
+            config.window_coefficients(),
+            config.fft_size(),
+            config.sample_rate,
+            analysis_range(config, line_spacing),
+            1.0 / (window_size as f32).sqrt(),
+        );
+        bin_spectrum(
+            &lines,
+            &config.layout,
+            config.interpolation,
+            config.aggregation,
+        )
+    }
+
+    fn method(&self) -> AnalysisMethod {
+        AnalysisMethod::Fft
+    }
+
+    fn full_scale_power(&self, config: &VisualizerConfig) -> f32 {
+        fft_full_scale_power(config)
+    }
+}
+
+/// FFTs of several window lengths, each covering one frequency region and
+/// all ending at the most recent sample: `bass_window` samples below
+/// `low_crossover`, `mid_window` samples up to `high_crossover` and
+/// `treble_window` samples above. Levels of tones match those of the main
+/// window across regions.
+pub struct MultiResolutionBackend {
+    bass_window: usize,
+    mid_window: usize,
+    treble_window: usize,
+    low_crossover: f32,
+    high_crossover: f32,
+    /// Compensated coefficients per region, with the function and length
+    /// they were computed for.
+    coefficients: Vec<(WindowFunction, Vec<f32>)>,
+}
+
+impl MultiResolutionBackend {
+    pub fn new(
+        bass_window: usize,
+        mid_window: usize,
+        treble_window: usize,
+        low_crossover: f32,
+        high_crossover: f32,
+    ) -> Self {
+        Self {
+            bass_window,
+            mid_window,
+            treble_window,
+            low_crossover,
+            high_crossover,
+            coefficients: Vec::new(),
+        }
+    }
+}
+
+impl SpectrumBackend for MultiResolutionBackend {
+    fn analyze(&mut self, samples: &[f32], config: &VisualizerConfig) -> Vec<f32> {
+        let window_size = config.window_size();
+        let sample_rate = config.sample_rate;
+        let window_function = config.window_function();
+        let main_fft = config.fft_size();
+        let (lowest, highest) = analysis_range(config, sample_rate as f32 / main_fft as f32);
+        let regions = [
+            (self.bass_window, lowest, self.low_crossover),
+            (self.mid_window, self.low_crossover, self.high_crossover),
+            (self.treble_window, self.high_crossover, highest),
+        ];
+        self.coefficients
+            .resize(regions.len(), (window_function, Vec::new()));
+
+        let mut lines = Vec::new();
+        for (i, &(len, low, high)) in regions.iter().enumerate() {
+            let (low, high) = (low.max(lowest), high.min(highest));
+            if low >= high {
+                continue;
+            }
+            let cached = &mut self.coefficients[i];
+            if cached.0 != window_function || cached.1.len() != len {
+                *cached = (
+                    window_function,
+                    window_function.compensated_coefficients(len),
+                );
+            }
+            let fft_size = (len.next_power_of_two() * config.zero_padding())
+                .min(MAX_FFT_SIZE)
+                .max(len.next_power_of_two());
+            // A tone reads A * sqrt(len) / 2 at 1 / sqrt(len) scaling; bring
+            // it to the level of the main window
+            let mut scale = (window_size as f32).sqrt() / len as f32;
+            if config.aggregation == BinAggregation::Sum {
+                // A summed tone also grows with the lines per Hz; match the
+                // padding of the main window
+                let padding = fft_size as f32 / len as f32;
+                let main_padding = main_fft as f32 / window_size as f32;
+                scale *= (main_padding / padding).sqrt();
+            }
+            let region = fft_lines(
+                &samples[samples.len() - len..],
+                &cached.1,
+                fft_size,
+                sample_rate,
+                (low, high),
+                scale,
+            );
+            // Regions meet at the crossover, which belongs to the upper one
+            let last = i == regions.len() - 1;
+            lines.extend(region.into_iter().filter(|&(freq, _)| last || freq < high));
+        }
+        bin_spectrum(
+            &lines,
+            &config.layout,
+            config.interpolation,
+            config.aggregation,
+        )
+    }
+
+    fn method(&self) -> AnalysisMethod {
+        AnalysisMethod::MultiResolution {
+            bass_window: self.bass_window,
+            mid_window: self.mid_window,
+            treble_window: self.treble_window,
+            low_crossover: self.low_crossover,
+            high_crossover: self.high_crossover,
+        }
+    }
+
+    /// Tones in every region are scaled to the level of the main window.
+    fn full_scale_power(&self, config: &VisualizerConfig) -> f32 {
+        fft_full_scale_power(config)
+    }
+}
+
+/// A sine of amplitude A peaks at A * sqrt(N) / 2 for a window of N samples
+/// with unit coherent gain. Summed over the bin, its main lobe adds up to
+/// the window's equivalent noise bandwidth in lines, times the padding of
+/// the window up to the FFT size.
+fn fft_full_scale_power(config: &VisualizerConfig) -> f32 {
+    let peak = config.window_size() as f32 / 4.0;
+    match config.aggregation {
+        BinAggregation::Sum => {
+            let coefficients = config.window_coefficients();
+            let enbw = coefficients.iter().map(|w| w * w).sum::<f32>() / coefficients.len() as f32;
+            peak * enbw * config.fft_size() as f32 / config.window_size() as f32
+        }
+        _ => peak,
+    }
+}
+
+/// Range of FFT lines the layout needs. Octave bands may reach past the
+/// configured range, and interpolation needs the lines `line_spacing` Hz
+/// beyond the outermost bands.
+fn analysis_range(config: &VisualizerConfig, line_spacing: f32) -> (f32, f32) {
+    let layout = &config.layout;
+    let edges = &layout.edges;
+    let nyquist = config.sample_rate as f32 / 2.0;
+    let lowest = (edges[0].min(layout.min_freq) - line_spacing).max(0.0);
+    let highest = (edges[edges.len() - 1].max(layout.max_freq) + line_spacing).min(nyquist);
+    (lowest, highest)
+}
+
+/// (frequency, magnitude) of the FFT lines within `range` of `samples`
+/// tapered by `coefficients` and zero-padded to `fft_size`, with magnitudes
+/// multiplied by `scale`.
+fn fft_lines(
+    samples: &[f32],
+    coefficients: &[f32],
+    fft_size: usize,
+    sample_rate: u32,
+    range: (f32, f32),
+    scale: f32,
+) -> Vec<(f32, f32)> {
+    let mut window = vec![0.0; fft_size];
+    for ((out, x), w) in window.iter_mut().zip(samples).zip(coefficients) {
+        *out = x * w;
+    }
+    let scaling = move |value: f32, _: &SpectrumDataStats| value * scale;
+    samples_fft_to_spectrum(
+        &window,
+        sample_rate,
+        FrequencyLimit::Range(range.0, range.1),
+        Some(&scaling),
+    )
+    .map(|spectrum| {
+        spectrum
+            .data()
+            .iter()
+            .map(|&(freq, mag)| (freq.val(), mag.val()))
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Direct constant-Q transform evaluated at the bin centers of the layout.
+/// Each bin correlates the most recent `q * sample_rate / center` samples,
+/// at most one window, with a tapered complex sinusoid.
+pub struct ConstantQBackend {
+    bins_per_octave: u32,
+    q: f32,
+    kernels: Vec<Kernel>,
+    /// Settings the kernels were built for.
+    kernel_key: Option<KernelKey>,
+}
+
+/// Tapered complex sinusoid of one bin, scaled so a sine at the center
+/// frequency reads its amplitude.
+struct Kernel {
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+struct KernelKey {
+    sample_rate: u32,
+    window_size: usize,
+    window_function: WindowFunction,
+    centers: Vec<f32>,
+}
+
+impl KernelKey {
+    fn new(config: &VisualizerConfig) -> Self {
+        Self {
+            sample_rate: config.sample_rate,
+            window_size: config.window_size(),
+            window_function: config.window_function(),
+            centers: config.layout.centers.clone(),
+        }
+    }
+
+    /// True if kernels built for this key fit `config`.
+    fn matches(&self, config: &VisualizerConfig) -> bool {
+        self.sample_rate == config.sample_rate
+            && self.window_size == config.window_size()
+            && self.window_function == config.window_function()
+            && self.centers == config.layout.centers
+    }
+}
+
+impl ConstantQBackend {
+    pub fn new(bins_per_octave: u32, q: f32) -> Self {
+        Self {
+            bins_per_octave,
+            q,
+            kernels: Vec::new(),
+            kernel_key: None,
+        }
+    }
+
+    fn build_kernels(&mut self, key: KernelKey) {
+        let sample_rate = key.sample_rate as f32;
+        self.kernels = key
+            .centers
+            .iter()
+            .map(|&center| {
+                let len =
+                    ((self.q * sample_rate / center).ceil() as usize).clamp(1, key.window_size);
+                let taper = key.window_function.coefficients(len);
+                let gain = taper.iter().sum::<f32>().max(f32::MIN_POSITIVE);
+                let omega = 2.0 * PI * center / sample_rate;
+                let (re, im) = taper
+                    .iter()
+                    .enumerate()
+                    .map(|(n, w)| {
+                        let phase = omega * n as f32;
+                        // Twice the amplitude, as a real sine splits between
+                        // positive and negative frequencies
+                        (2.0 * w * phase.cos() / gain, -2.0 * w * phase.sin() / gain)
+                    })
+                    .unzip();
+                Kernel { re, im }
+            })
+            .collect();
+        self.kernel_key = Some(key);
+    }
+}
+
+impl SpectrumBackend for ConstantQBackend {
+    fn analyze(&mut self, samples: &[f32], config: &VisualizerConfig) -> Vec<f32> {
+        if !self
+            .kernel_key
+            .as_ref()
+            .is_some_and(|key| key.matches(config))
+        {
+            self.build_kernels(KernelKey::new(config));
+        }
+
+        let window = &samples[..config.window_size()];
+        self.kernels
+            .iter()
+            .map(|kernel| {
+                // Align each kernel with the end of the window
+                let recent = &window[window.len() - kernel.re.len()..];
+                let (re, im) = recent
+                    .iter()
+                    .zip(kernel.re.iter().zip(&kernel.im))
+                    .fold((0.0, 0.0), |(re, im), (x, (kr, ki))| {
+                        (re + x * kr, im + x * ki)
+                    });
+                re * re + im * im
+            })
+            .collect()
+    }
+
+    fn method(&self) -> AnalysisMethod {
+        AnalysisMethod::ConstantQ {
+            bins_per_octave: self.bins_per_octave,
+            q: self.q,
+        }
+    }
+
+    fn full_scale_power(&self, _config: &VisualizerConfig) -> f32 {
+        // Kernels read the amplitude directly
+        1.0
+    }
+}
@@ -0,0 +1,291 @@
+use cpal::traits::{DeviceTrait, HostTrait};
+
+use crate::AudioError;
+
+/// Sample formats that sources and players can convert from and to.
+pub const SAMPLE_FORMATS: [cpal::SampleFormat; 8] = [
+    cpal::SampleFormat::I8,
+    cpal::SampleFormat::I16,
+    cpal::SampleFormat::I32,
+    cpal::SampleFormat::U8,
+    cpal::SampleFormat::U16,
+    cpal::SampleFormat::U32,
+    cpal::SampleFormat::F32,
+    cpal::SampleFormat::F64,
+];
+
+/// Stream parameters to use instead of the device defaults.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamOptions {
+    /// Frames per callback; the driver chooses when `None`.
+    pub buffer_size: Option<u32>,
+    pub sample_format: Option<cpal::SampleFormat>,
+}
+
+/// A stream configuration chosen for a device.
+#[derive(Clone, Debug)]
+pub struct StreamSetup {
+    pub config: cpal::StreamConfig,
+    pub sample_format: cpal::SampleFormat,
+}
+
+/// One supported configuration range of a device.
+#[derive(Clone, Debug)]
+pub struct ConfigInfo {
+    pub channels: u16,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+    pub sample_format: cpal::SampleFormat,
+    /// Supported frames per callback, if the driver reports them.
+    pub buffer_size: Option<(u32, u32)>,
+}
+
+impl ConfigInfo {
+    fn from_range(range: &cpal::SupportedStreamConfigRange) -> Self {
+        let buffer_size = match *range.buffer_size() {
+            cpal::SupportedBufferSize::Range { min, max } => Some((min, max)),
+            cpal::SupportedBufferSize::Unknown => None,
+        };
+        Self {
+            channels: range.channels(),
+            min_sample_rate: range.min_sample_rate().0,
+            max_sample_rate: range.max_sample_rate().0,
+            sample_format: range.sample_format(),
+            buffer_size,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ch, ", self.channels)?;
+        if self.min_sample_rate == self.max_sample_rate {
+            write!(f, "{} Hz", self.min_sample_rate)?;
+        } else {
+            write!(f, "{}-{} Hz", self.min_sample_rate, self.max_sample_rate)?;
+        }
+        write!(f, ", {}", self.sample_format)?;
+        if let Some((min, max)) = self.buffer_size {
+            write!(f, ", buffer {}-{}", min, max)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    pub configs: Vec<ConfigInfo>,
+}
+
+/// Devices of one audio host (e.g. ALSA or JACK).
+#[derive(Clone, Debug)]
+pub struct HostInfo {
+    pub id: cpal::HostId,
+    pub is_default: bool,
+    pub inputs: Vec<DeviceInfo>,
+    pub outputs: Vec<DeviceInfo>,
+}
+
+/// Enumerate the available hosts with their input and output devices.
+/// Hosts that fail to initialize are skipped.
+pub fn list_hosts() -> Vec<HostInfo> {
+    let default_id = cpal::default_host().id();
+    cpal::available_hosts()
+        .into_iter()
+        .filter_map(|id| cpal::host_from_id(id).ok())
+        .map(|host| {
+            let default_input = host.default_input_device().and_then(|d| d.name().ok());
+            let default_output = host.default_output_device().and_then(|d| d.name().ok());
+            let inputs = host
+                .input_devices()
+                .map(|devices| {
+                    devices
+                        .filter_map(|d| describe(&d, true, default_input.as_deref()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let outputs = host
+                .output_devices()
+                .map(|devices| {
+                    devices
+                        .filter_map(|d| describe(&d, false, default_output.as_deref()))
+                        .collect()
+                })
+                .unwrap_or_default();
+            HostInfo {
+                id: host.id(),
+                is_default: host.id() == default_id,
+                inputs,
+                outputs,
+            }
+        })
+        .collect()
+}
+
+fn describe(device: &cpal::Device, input: bool, default: Option<&str>) -> Option<DeviceInfo> {
+    let name = device.name().ok()?;
+    let configs = if input {
+        device
+            .supported_input_configs()
+            .map(|c| c.map(|r| ConfigInfo::from_range(&r)).collect())
+    } else {
+        device
+            .supported_output_configs()
+            .map(|c| c.map(|r| ConfigInfo::from_range(&r)).collect())
+    };
+    Some(DeviceInfo {
+        is_default: default == Some(name.as_str()),
+        name,
+        configs: configs.unwrap_or_default(),
+    })
+}
+
+/// Find a host by name (case-insensitive), e.g. "alsa" or "jack".
+pub fn find_host(name: &str) -> Result<cpal::HostId, AudioError> {
+    cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name))
+        .ok_or_else(|| AudioError::HostNotFound(name.to_string()))
+}
+
+/// Open the host with `id`, or the default host.
+pub fn host(id: Option<cpal::HostId>) -> Result<cpal::Host, AudioError> {
+    match id {
+        Some(id) => Ok(cpal::host_from_id(id)?),
+        None => Ok(cpal::default_host()),
+    }
+}
+
+/// Parse a sample format name such as `f32` or `i16`.
+pub fn parse_sample_format(name: &str) -> Option<cpal::SampleFormat> {
+    SAMPLE_FORMATS
+        .into_iter()
+        .find(|format| format.to_string().eq_ignore_ascii_case(name))
+}
+
+/// Pick a device by name: an exact match wins, otherwise the first device
+/// whose name contains `name` (case-insensitive).
+fn find_device(
+    devices: impl Iterator<Item = cpal::Device>,
+    name: &str,
+) -> Result<cpal::Device, AudioError> {
+    let needle = name.to_lowercase();
+    let mut partial = None;
+    for device in devices {
+        let Ok(device_name) = device.name() else {
+            continue;
+        };
+        if device_name == name {
+            return Ok(device);
+        }
+        if partial.is_none() && device_name.to_lowercase().contains(&needle) {
+            partial = Some(device);
+        }
+    }
+    partial.ok_or_else(|| AudioError::DeviceNotFound(name.to_string()))
+}
+
+/// The input device matching `name`, or the default input device.
+pub fn input_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device, AudioError> {
+    match name {
+        Some(name) => find_device(host.input_devices()?, name),
+        None => host.default_input_device().ok_or(AudioError::NoInputDevice),
+    }
+}
+
+/// The output device matching `name`, or the default output device.
+pub fn output_device(host: &cpal::Host, name: Option<&str>) -> Result<cpal::Device, AudioError> {
+    match name {
+        Some(name) => find_device(host.output_devices()?, name),
+        None => host
+            .default_output_device()
+            .ok_or(AudioError::NoOutputDevice),
+    }
+}
+
+/// Input stream configuration for `device`: the default configuration with
+/// the sample format and buffer size from `options` applied.
+pub fn input_setup(
+    device: &cpal::Device,
+    options: &StreamOptions,
+) -> Result<StreamSetup, AudioError> {
+    let default = device.default_input_config()?;
+    let ranges: Vec<_> = device
+        .supported_input_configs()
+        .map(|c| c.collect())
+        .unwrap_or_default();
+    choose_setup(default, &ranges, None, options)
+}
+
+/// Output stream configuration for `device` at `sample_rate` (or the default
+/// rate) with the options applied.
+pub fn output_setup(
+    device: &cpal::Device,
+    sample_rate: Option<u32>,
+    options: &StreamOptions,
+) -> Result<StreamSetup, AudioError> {
+    let default = device.default_output_config()?;
+    let ranges: Vec<_> = device
+        .supported_output_configs()
+        .map(|c| c.collect())
+        .unwrap_or_default();
+    choose_setup(default, &ranges, sample_rate, options)
+}
+
+fn choose_setup(
+    default: cpal::SupportedStreamConfig,
+    ranges: &[cpal::SupportedStreamConfigRange],
+    sample_rate: Option<u32>,
+    options: &StreamOptions,
+) -> Result<StreamSetup, AudioError> {
+    let sample_format = options.sample_format.unwrap_or(default.sample_format());
+    if !SAMPLE_FORMATS.contains(&sample_format) {
+        return Err(AudioError::UnsupportedSampleFormat(sample_format));
+    }
+    let sample_rate = sample_rate.unwrap_or(default.sample_rate().0);
+
+    // Prefer the default channel count, then any range with the format
+    let matches = |range: &&cpal::SupportedStreamConfigRange| {
+        range.sample_format() == sample_format
+            && (range.min_sample_rate().0..=range.max_sample_rate().0).contains(&sample_rate)
+    };
+    let range = ranges
+        .iter()
+        .filter(matches)
+        .find(|range| range.channels() == default.channels())
+        .or_else(|| ranges.iter().find(matches));
+
+    let (channels, buffer_range) = match range {
+        Some(range) => (range.channels(), *range.buffer_size()),
+        // Drivers that report no ranges get the defaults as requested
+        None if ranges.is_empty()
+            || (sample_format == default.sample_format()
+                && sample_rate == default.sample_rate().0) =>
+        {
+            (default.channels(), *default.buffer_size())
+        }
+        None if ranges.iter().any(|r| r.sample_format() == sample_format) => {
+            return Err(AudioError::UnsupportedSampleRate(sample_rate));
+        }
+        None => return Err(AudioError::UnsupportedSampleFormat(sample_format)),
+    };
+
+    let buffer_size = match (options.buffer_size, buffer_range) {
+        (None, _) => cpal::BufferSize::Default,
+        (Some(frames), cpal::SupportedBufferSize::Range { min, max }) => {
+            cpal::BufferSize::Fixed(frames.clamp(min, max))
+        }
+        (Some(frames), cpal::SupportedBufferSize::Unknown) => cpal::BufferSize::Fixed(frames),
+    };
+
+    Ok(StreamSetup {
+        config: cpal::StreamConfig {
+            channels,
+            sample_rate: cpal::SampleRate(sample_rate),
+            buffer_size,
+        },
+        sample_format,
+    })
+}
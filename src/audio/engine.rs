@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use cpal::traits::DeviceTrait;
+
+use crate::audio::device::{self, StreamOptions};
+use crate::audio::{
+    AudioSource, FileSource, GeneratorSettings, GeneratorSource, InputSource, Player,
+};
+use crate::{AudioError, Transport, Visualizer};
+
+/// Where an `AudioEngine` gets its audio from.
+#[derive(Clone, Debug)]
+pub enum SourceSpec {
+    /// Decode a file and play it on the output device.
+    File(PathBuf),
+    /// Capture from the input device.
+    Live,
+    /// Play a test signal on the output device, generated at `rate` or the
+    /// device rate.
+    Generator {
+        settings: GeneratorSettings,
+        rate: Option<u32>,
+    },
+}
+
+/// Audio host, devices and stream options. `None` selects the default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeviceSelection {
+    pub host: Option<cpal::HostId>,
+    pub input: Option<String>,
+    pub output: Option<String>,
+    pub options: StreamOptions,
+}
+
+/// Runs a source on the selected devices and feeds a shared visualizer.
+/// The engine can be restarted with a different device selection while the
+/// visualizer and transport stay the same.
+pub struct AudioEngine {
+    source: SourceSpec,
+    selection: DeviceSelection,
+    visualizer: Arc<Mutex<Visualizer>>,
+    transport: Arc<Transport>,
+    generator: Option<Arc<Mutex<GeneratorSettings>>>,
+    player: Option<Player>,
+    input_stream: Option<cpal::Stream>,
+    device_name: Option<String>,
+}
+
+impl AudioEngine {
+    /// Create an engine; audio starts with `start`.
+    pub fn new(
+        source: SourceSpec,
+        selection: DeviceSelection,
+        visualizer: Arc<Mutex<Visualizer>>,
+    ) -> Self {
+        let sample_rate = visualizer
+            .lock()
+            .map_or(44100, |vis| vis.config.sample_rate);
+        let generator = match &source {
+            SourceSpec::Generator { settings, .. } => Some(Arc::new(Mutex::new(settings.clone()))),
+            _ => None,
+        };
+        Self {
+            source,
+            selection,
+            visualizer,
+            transport: Arc::new(Transport::new(sample_rate, None)),
+            generator,
+            player: None,
+            input_stream: None,
+            device_name: None,
+        }
+    }
+
+    /// Open the selected devices and start the source, stopping any running audio first.
+    /// A file resumes at the current transport position.
+    pub fn start(&mut self) -> Result<(), AudioError> {
+        self.stop();
+        let host = device::host(self.selection.host)?;
+        let options = self.selection.options;
+
+        let (device, player) = match &self.source {
+            SourceSpec::Live => {
+                let device = device::input_device(&host, self.selection.input.as_deref())?;
+                let (source, stream) = InputSource::open_with_options(&device, &options)?;
+                self.attach(&source);
+                let player = Player::analyze(
+                    Box::new(source),
+                    self.visualizer.clone(),
+                    self.transport.clone(),
+                );
+                self.input_stream = Some(stream);
+                (device, player)
+            }
+            SourceSpec::File(path) => {
+                let device = device::output_device(&host, self.selection.output.as_deref())?;
+                // Decode resampled to the rate the device will run at
+                let rate = device::output_setup(&device, None, &options)?
+                    .config
+                    .sample_rate
+                    .0;
+                let source = FileSource::open(path, rate)?;
+                let player = self.play(&device, source)?;
+                (device, player)
+            }
+            SourceSpec::Generator { rate, .. } => {
+                let device = device::output_device(&host, self.selection.output.as_deref())?;
+                // The stream runs at the generator's rate, so a requested rate
+                // must be one the device supports
+                let rate = device::output_setup(&device, *rate, &options)?
+                    .config
+                    .sample_rate
+                    .0;
+                let settings = self.generator.clone().unwrap_or_default();
+                let source = GeneratorSource::with_settings(settings, rate, 1);
+                let player = self.play(&device, source)?;
+                (device, player)
+            }
+        };
+
+        self.player = Some(player);
+        self.device_name = device.name().ok();
+        Ok(())
+    }
+
+    /// Stop playback or capture.
+    pub fn stop(&mut self) {
+        self.player = None;
+        self.input_stream = None;
+        self.device_name = None;
+    }
+
+    /// Switch to `selection` and restart.
+    pub fn set_selection(&mut self, selection: DeviceSelection) -> Result<(), AudioError> {
+        self.selection = selection;
+        self.start()
+    }
+
+    pub fn selection(&self) -> &DeviceSelection {
+        &self.selection
+    }
+
+    pub fn source(&self) -> &SourceSpec {
+        &self.source
+    }
+
+    /// True if the source captures from an input device rather than playing
+    /// on an output device.
+    pub fn uses_input(&self) -> bool {
+        matches!(self.source, SourceSpec::Live)
+    }
+
+    /// Name of the device in use, if running.
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    pub fn visualizer(&self) -> Arc<Mutex<Visualizer>> {
+        self.visualizer.clone()
+    }
+
+    pub fn transport(&self) -> Arc<Transport> {
+        self.transport.clone()
+    }
+
+    /// Settings of the test signal generator, if that is the source.
+    pub fn generator(&self) -> Option<Arc<Mutex<GeneratorSettings>>> {
+        self.generator.clone()
+    }
+
+    fn play(
+        &self,
+        device: &cpal::Device,
+        source: impl AudioSource + 'static,
+    ) -> Result<Player, AudioError> {
+        self.attach(&source);
+        Player::play_with_options(
+            device,
+            &self.selection.options,
+            Box::new(source),
+            self.visualizer.clone(),
+            self.transport.clone(),
+        )
+    }
+
+    /// Adapt the visualizer and transport to a newly opened source.
+    fn attach(&self, source: &dyn AudioSource) {
+        if let Ok(mut vis) = self.visualizer.lock() {
+            vis.config.set_sample_rate(source.sample_rate());
+            vis.config.channels = source.channels();
+        }
+        self.transport
+            .reset(source.sample_rate(), source.duration());
+    }
+}
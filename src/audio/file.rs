@@ -0,0 +1,317 @@
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, Decoder, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+use symphonia::default::{get_codecs, get_probe};
+
+use crate::AudioError;
+use crate::audio::AudioSource;
+use crate::audio::resampler::Resampler;
+use crate::audio::ring::SampleRing;
+
+/// Seconds of decoded audio buffered ahead of playback.
+const BUFFER_SECONDS: usize = 1;
+
+/// Audio file decoded on a background thread.
+/// Decoded samples are resampled to the playback rate and delivered interleaved
+/// through a bounded ring buffer, so memory use stays constant regardless of
+/// file length.
+pub struct FileSource {
+    ring: Arc<SampleRing>,
+    commands: Sender<DecoderCommand>,
+    errors: Receiver<AudioError>,
+    /// Playback sample rate of the delivered samples.
+    sample_rate: u32,
+    channels: usize,
+    /// Track length in frames at `sample_rate`, if the container reports it.
+    duration: Option<u64>,
+}
+
+enum DecoderCommand {
+    Seek { frame: u64, epoch: u64 },
+}
+
+impl AudioSource for FileSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Fewer samples than requested means the decoder is behind or the file has ended.
+    fn read(&mut self, out: &mut [f32]) -> usize {
+        // The ring only ever holds whole frames
+        self.ring.pop(out)
+    }
+
+    fn duration(&self) -> Option<u64> {
+        self.duration
+    }
+
+    /// Buffered samples are dropped and decoding resumes from the new
+    /// position on the background thread.
+    fn seek(&mut self, frame: u64) -> bool {
+        let epoch = self.ring.reset();
+        self.commands
+            .send(DecoderCommand::Seek { frame, epoch })
+            .is_ok()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.ring.is_drained()
+    }
+
+    /// Decoding stops after a fatal error and the track plays out as if it ended.
+    fn take_error(&mut self) -> Option<AudioError> {
+        self.errors.try_recv().ok()
+    }
+}
+
+impl Drop for FileSource {
+    fn drop(&mut self) {
+        // Stop the decoder thread if it is waiting for space
+        self.ring.close();
+    }
+}
+
+impl FileSource {
+    /// Start decoding `path` on a background thread, resampling to `output_rate`.
+    pub fn open(path: impl AsRef<Path>, output_rate: u32) -> Result<Self, AudioError> {
+        // Open the media source.
+        let path = path.as_ref();
+        let src = File::open(path).map_err(AudioError::Open)?;
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
+
+        let mss = MediaSourceStream::new(Box::new(src), Default::default());
+
+        let mut hint = Hint::new();
+        hint.with_extension(extension);
+
+        // Use the default options for metadata and format readers.
+        let meta_opts: MetadataOptions = Default::default();
+        let fmt_opts: FormatOptions = Default::default();
+
+        let probe = get_probe()
+            .format(&hint, mss, &fmt_opts, &meta_opts)
+            .map_err(AudioError::UnsupportedFormat)?;
+
+        // Get the instantiated format reader.
+        let format = probe.format;
+
+        // Find the first audio track with a known (decodeable) codec.
+        let track = format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or(AudioError::NoSupportedTrack)?;
+        let track_id = track.id;
+
+        // Use the default options for the decoder.
+        let dec_opts: DecoderOptions = Default::default();
+
+        // Create a decoder for the track.
+        let decoder = get_codecs()
+            .make(&track.codec_params, &dec_opts)
+            .map_err(AudioError::UnsupportedCodec)?;
+
+        let file_rate = decoder
+            .codec_params()
+            .sample_rate
+            .ok_or(AudioError::MissingSampleRate)?;
+        let num_channels = decoder
+            .codec_params()
+            .channels
+            .ok_or(AudioError::MissingChannelLayout)?
+            .count();
+        let duration = decoder
+            .codec_params()
+            .n_frames
+            .map(|n| n * output_rate as u64 / file_rate as u64);
+        println!("Streaming file: {}", path.display());
+
+        if file_rate != output_rate {
+            println!("Resampling {} Hz -> {} Hz", file_rate, output_rate);
+        }
+
+        let ring = Arc::new(SampleRing::new(
+            output_rate as usize * num_channels * BUFFER_SECONDS,
+        ));
+        let (commands, command_rx) = mpsc::channel();
+        let (error_tx, errors) = mpsc::channel();
+        let decode_thread = DecodeThread {
+            format,
+            decoder,
+            track_id,
+            output_rate,
+            num_channels,
+            resampler: Resampler::new(file_rate, output_rate, num_channels),
+            ring: ring.clone(),
+            commands: command_rx,
+            errors: error_tx,
+        };
+        thread::spawn(move || decode_thread.run());
+
+        Ok(FileSource {
+            ring,
+            commands,
+            errors,
+            sample_rate: output_rate,
+            channels: num_channels,
+            duration,
+        })
+    }
+}
+
+/// Background side of a `FileSource`: pulls packets, decodes them and
+/// pushes resampled interleaved samples into the ring until playback goes away.
+struct DecodeThread {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    output_rate: u32,
+    num_channels: usize,
+    resampler: Resampler,
+    ring: Arc<SampleRing>,
+    commands: Receiver<DecoderCommand>,
+    errors: Sender<AudioError>,
+}
+
+impl DecodeThread {
+    fn run(mut self) {
+        let mut epoch = 0;
+        let mut at_end = false;
+        // Timestamp the last seek asked for; earlier frames are skipped
+        let mut required_ts = 0;
+        let mut resampled: Vec<f32> = Vec::new();
+
+        loop {
+            // Apply pending seeks; once the stream has ended, wait for one
+            let command = if at_end {
+                match self.commands.recv() {
+                    Ok(command) => Some(command),
+                    Err(_) => return,
+                }
+            } else {
+                match self.commands.try_recv() {
+                    Ok(command) => Some(command),
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            };
+
+            if let Some(DecoderCommand::Seek {
+                frame,
+                epoch: new_epoch,
+            }) = command
+            {
+                epoch = new_epoch;
+                at_end = false;
+                let rate = self.output_rate as u64;
+                let time = Time::new(frame / rate, (frame % rate) as f64 / rate as f64);
+                let seek_to = SeekTo::Time {
+                    time,
+                    track_id: Some(self.track_id),
+                };
+                match self.format.seek(SeekMode::Accurate, seek_to) {
+                    Ok(seeked) => {
+                        self.decoder.reset();
+                        self.resampler.reset();
+                        required_ts = seeked.required_ts;
+                    }
+                    Err(_) => {
+                        // Seeking past the end
+                        self.ring.finish(epoch);
+                        at_end = true;
+                    }
+                }
+                continue;
+            }
+
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::ResetRequired) => {
+                    self.decoder.reset();
+                    continue;
+                }
+                Err(err) => {
+                    // An unexpected EOF is the regular end of the stream.
+                    if !Self::is_end_of_stream(&err) {
+                        let _ = self.errors.send(AudioError::Decode(err));
+                    }
+                    self.finish(epoch, &mut resampled);
+                    at_end = true;
+                    continue;
+                }
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+
+            // Decode the packet into audio samples.
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                Err(SymphoniaError::DecodeError(err)) => {
+                    // Corrupt packet; skip it and keep playing
+                    eprintln!("Skipping undecodable packet: {}", err);
+                    continue;
+                }
+                Err(SymphoniaError::IoError(err)) => {
+                    eprintln!("Skipping truncated packet: {}", err);
+                    continue;
+                }
+                Err(SymphoniaError::ResetRequired) => {
+                    self.decoder.reset();
+                    continue;
+                }
+                Err(err) => {
+                    let _ = self.errors.send(AudioError::Decode(err));
+                    self.finish(epoch, &mut resampled);
+                    at_end = true;
+                    continue;
+                }
+            };
+            let mut sample_buffer =
+                SampleBuffer::<f32>::new(decoded.capacity() as u64, *decoded.spec());
+
+            sample_buffer.copy_interleaved_ref(decoded);
+
+            // Skip frames before an accurate seek target (timestamps count frames)
+            let skip = required_ts.saturating_sub(packet.ts()) as usize;
+
+            let samples = sample_buffer.samples();
+            let skip = (skip * self.num_channels).min(samples.len());
+
+            resampled.clear();
+            self.resampler.process(&samples[skip..], &mut resampled);
+
+            // Blocks while the ring is full; stops when playback is gone.
+            if !self.ring.push(epoch, &resampled) {
+                return;
+            }
+        }
+    }
+
+    /// Push the frames the resampler still holds and mark the stream as ended.
+    fn finish(&mut self, epoch: u64, resampled: &mut Vec<f32>) {
+        resampled.clear();
+        self.resampler.flush(resampled);
+        self.ring.push(epoch, resampled);
+        self.ring.finish(epoch);
+    }
+
+    fn is_end_of_stream(err: &SymphoniaError) -> bool {
+        matches!(err, SymphoniaError::IoError(e) if e.kind() == std::io::ErrorKind::UnexpectedEof)
+    }
+}
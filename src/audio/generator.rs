@@ -0,0 +1,417 @@
+use std::f64::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use crate::audio::AudioSource;
+
+/// Synthetic test signal produced by a `GeneratorSource`.
+/// Frequencies are in Hz, durations in seconds.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Signal {
+    Sine {
+        frequency: f32,
+    },
+    /// Logarithmic sine sweep from `start` to `end`, restarted every `duration` seconds.
+    Sweep {
+        start: f32,
+        end: f32,
+        duration: f32,
+    },
+    WhiteNoise,
+    /// Noise falling at 3 dB per octave.
+    PinkNoise,
+    /// Noise falling at 6 dB per octave.
+    BrownNoise,
+    Square {
+        frequency: f32,
+    },
+    Saw {
+        frequency: f32,
+    },
+    /// Single-sample clicks at `frequency` per second.
+    Impulse {
+        frequency: f32,
+    },
+    /// Equal-level sines at each frequency.
+    MultiTone {
+        frequencies: Vec<f32>,
+    },
+}
+
+impl Signal {
+    /// One signal of every kind with default parameters.
+    pub fn options() -> Vec<Signal> {
+        vec![
+            Signal::Sine { frequency: 1000.0 },
+            Signal::Sweep {
+                start: 20.0,
+                end: 20000.0,
+                duration: 10.0,
+            },
+            Signal::WhiteNoise,
+            Signal::PinkNoise,
+            Signal::BrownNoise,
+            Signal::Square { frequency: 440.0 },
+            Signal::Saw { frequency: 440.0 },
+            Signal::Impulse { frequency: 10.0 },
+            Signal::MultiTone {
+                frequencies: vec![100.0, 1000.0, 10000.0],
+            },
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Signal::Sine { .. } => "Sine",
+            Signal::Sweep { .. } => "Log sweep",
+            Signal::WhiteNoise => "White noise",
+            Signal::PinkNoise => "Pink noise",
+            Signal::BrownNoise => "Brown noise",
+            Signal::Square { .. } => "Square",
+            Signal::Saw { .. } => "Saw",
+            Signal::Impulse { .. } => "Impulse train",
+            Signal::MultiTone { .. } => "Multi-tone",
+        }
+    }
+
+    /// True if `other` is the same kind of signal, regardless of parameters.
+    pub fn same_kind(&self, other: &Signal) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl fmt::Display for Signal {
+    /// Formats in the syntax accepted by `from_str`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Sine { frequency } => write!(f, "sine:{}", frequency),
+            Signal::Sweep {
+                start,
+                end,
+                duration,
+            } => write!(f, "sweep:{}:{}:{}", start, end, duration),
+            Signal::WhiteNoise => write!(f, "white"),
+            Signal::PinkNoise => write!(f, "pink"),
+            Signal::BrownNoise => write!(f, "brown"),
+            Signal::Square { frequency } => write!(f, "square:{}", frequency),
+            Signal::Saw { frequency } => write!(f, "saw:{}", frequency),
+            Signal::Impulse { frequency } => write!(f, "impulse:{}", frequency),
+            Signal::MultiTone { frequencies } => {
+                let list: Vec<String> = frequencies.iter().map(|f| f.to_string()).collect();
+                write!(f, "multitone:{}", list.join(","))
+            }
+        }
+    }
+}
+
+impl FromStr for Signal {
+    type Err = String;
+
+    /// Parse `kind[:params]`, e.g. `sine:1000`, `sweep:20:20000:10`, `pink`,
+    /// `impulse:4` or `multitone:100,1000,5000`. Omitted parameters use the
+    /// defaults from `Signal::options`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let kind = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let params: Vec<&str> = parts.collect();
+
+        let number = |index: usize, default: f32| -> Result<f32, String> {
+            match params.get(index) {
+                None => Ok(default),
+                Some(p) => match p.trim().parse::<f32>() {
+                    Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+                    _ => Err(format!("invalid number '{}' in signal '{}'", p, s)),
+                },
+            }
+        };
+        let expect_params = |max: usize| {
+            if params.len() > max {
+                Err(format!("too many parameters for signal '{}'", s))
+            } else {
+                Ok(())
+            }
+        };
+
+        let signal = match kind.as_str() {
+            "sine" => {
+                expect_params(1)?;
+                Signal::Sine {
+                    frequency: number(0, 1000.0)?,
+                }
+            }
+            "sweep" => {
+                expect_params(3)?;
+                Signal::Sweep {
+                    start: number(0, 20.0)?,
+                    end: number(1, 20000.0)?,
+                    duration: number(2, 10.0)?,
+                }
+            }
+            "white" => {
+                expect_params(0)?;
+                Signal::WhiteNoise
+            }
+            "pink" => {
+                expect_params(0)?;
+                Signal::PinkNoise
+            }
+            "brown" => {
+                expect_params(0)?;
+                Signal::BrownNoise
+            }
+            "square" => {
+                expect_params(1)?;
+                Signal::Square {
+                    frequency: number(0, 440.0)?,
+                }
+            }
+            "saw" => {
+                expect_params(1)?;
+                Signal::Saw {
+                    frequency: number(0, 440.0)?,
+                }
+            }
+            "impulse" => {
+                expect_params(1)?;
+                Signal::Impulse {
+                    frequency: number(0, 10.0)?,
+                }
+            }
+            "multitone" => {
+                expect_params(1)?;
+                let frequencies = match params.first() {
+                    None => vec![100.0, 1000.0, 10000.0],
+                    Some(list) => list
+                        .split(',')
+                        .map(|f| match f.trim().parse::<f32>() {
+                            Ok(value) if value.is_finite() && value > 0.0 => Ok(value),
+                            _ => Err(format!("invalid number '{}' in signal '{}'", f, s)),
+                        })
+                        .collect::<Result<Vec<f32>, String>>()?,
+                };
+                Signal::MultiTone { frequencies }
+            }
+            _ => {
+                return Err(format!(
+                    "unknown signal '{}' (expected sine, sweep, white, pink, brown, \
+                     square, saw, impulse or multitone)",
+                    kind
+                ));
+            }
+        };
+        Ok(signal)
+    }
+}
+
+/// Generator parameters shared between a `GeneratorSource` and its controls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratorSettings {
+    pub signal: Signal,
+    /// Peak level in dBFS (0 = full scale).
+    pub level_db: f32,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        Self {
+            signal: Signal::Sine { frequency: 1000.0 },
+            level_db: -12.0,
+        }
+    }
+}
+
+impl GeneratorSettings {
+    /// Peak amplitude for `level_db`.
+    pub fn amplitude(&self) -> f32 {
+        10f32.powf(self.level_db / 20.0)
+    }
+}
+
+/// Endless synthetic signal, written identically to every channel.
+/// Changes to the shared settings take effect on the next read.
+pub struct GeneratorSource {
+    settings: Arc<Mutex<GeneratorSettings>>,
+    current: GeneratorSettings,
+    sample_rate: u32,
+    channels: usize,
+    /// Oscillator phase in cycles (0..1).
+    phase: f64,
+    /// Per-tone phases for multi-tone signals, in cycles.
+    tone_phases: Vec<f64>,
+    /// Seconds into the current sweep.
+    sweep_time: f64,
+    rng: u64,
+    /// Filter state for pink and brown noise.
+    noise_state: [f32; 3],
+}
+
+impl GeneratorSource {
+    pub fn new(settings: GeneratorSettings, sample_rate: u32, channels: usize) -> Self {
+        Self::with_settings(Arc::new(Mutex::new(settings)), sample_rate, channels)
+    }
+
+    /// Create a source controlled by existing shared settings.
+    pub fn with_settings(
+        settings: Arc<Mutex<GeneratorSettings>>,
+        sample_rate: u32,
+        channels: usize,
+    ) -> Self {
+        let current = settings.lock().map(|s| s.clone()).unwrap_or_default();
+        Self {
+            settings,
+            current,
+            sample_rate: sample_rate.max(1),
+            channels: channels.max(1),
+            phase: 0.0,
+            tone_phases: Vec::new(),
+            sweep_time: 0.0,
+            rng: 0x2545_f491_4f6c_dd1d,
+            noise_state: [0.0; 3],
+        }
+    }
+
+    /// Handle for changing the signal and level while the source is playing.
+    pub fn settings(&self) -> Arc<Mutex<GeneratorSettings>> {
+        self.settings.clone()
+    }
+
+    /// Pick up changed settings, restarting the oscillators on a new signal.
+    fn sync_settings(&mut self) {
+        // Keep the current settings rather than block if the UI holds the lock
+        let Ok(settings) = self.settings.try_lock() else {
+            return;
+        };
+        if *settings == self.current {
+            return;
+        }
+        if settings.signal != self.current.signal {
+            self.phase = 0.0;
+            self.tone_phases.clear();
+            self.sweep_time = 0.0;
+            self.noise_state = [0.0; 3];
+        }
+        self.current = settings.clone();
+    }
+
+    /// Uniform white noise in [-1, 1) from an xorshift generator.
+    fn white(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    /// Advance the oscillator phase by one sample at `frequency` and return
+    /// the phase before the step along with the step size (both in cycles).
+    fn step(&mut self, frequency: f32) -> (f64, f64) {
+        let dt = (frequency as f64 / self.sample_rate as f64).min(0.5);
+        let t = self.phase;
+        self.phase = (self.phase + dt).fract();
+        (t, dt)
+    }
+
+    fn next_sample(&mut self) -> f32 {
+        match self.current.signal {
+            Signal::Sine { frequency } => {
+                let (t, _) = self.step(frequency);
+                (t * TAU).sin() as f32
+            }
+            Signal::Sweep {
+                start,
+                end,
+                duration,
+            } => {
+                let duration = duration.max(0.1) as f64;
+                let ratio = (end / start) as f64;
+                let frequency = start as f64 * ratio.powf(self.sweep_time / duration);
+                self.sweep_time += 1.0 / self.sample_rate as f64;
+                if self.sweep_time >= duration {
+                    self.sweep_time = 0.0;
+                    self.phase = 0.0;
+                }
+                let (t, _) = self.step(frequency as f32);
+                (t * TAU).sin() as f32
+            }
+            Signal::WhiteNoise => self.white(),
+            Signal::PinkNoise => {
+                // Paul Kellet's economy filter, roughly -3 dB per octave
+                let white = self.white();
+                let [b0, b1, b2] = &mut self.noise_state;
+                *b0 = 0.99765 * *b0 + white * 0.099_046;
+                *b1 = 0.963 * *b1 + white * 0.296_516_4;
+                *b2 = 0.57 * *b2 + white * 1.052_691_3;
+                ((*b0 + *b1 + *b2 + white * 0.1848) * 0.11).clamp(-1.0, 1.0)
+            }
+            Signal::BrownNoise => {
+                // Leaky integrator, -6 dB per octave above a few Hz
+                let white = self.white();
+                let b = &mut self.noise_state[0];
+                *b = (*b + 0.02 * white) / 1.02;
+                (*b * 3.5).clamp(-1.0, 1.0)
+            }
+            Signal::Square { frequency } => {
+                let (t, dt) = self.step(frequency);
+                let naive = if t < 0.5 { 1.0 } else { -1.0 };
+                (naive + poly_blep(t, dt) - poly_blep((t + 0.5).fract(), dt)) as f32
+            }
+            Signal::Saw { frequency } => {
+                let (t, dt) = self.step(frequency);
+                (2.0 * t - 1.0 - poly_blep(t, dt)) as f32
+            }
+            Signal::Impulse { frequency } => {
+                let (t, dt) = self.step(frequency);
+                if t < dt { 1.0 } else { 0.0 }
+            }
+            Signal::MultiTone { ref frequencies } => {
+                let count = frequencies.len();
+                if count == 0 {
+                    return 0.0;
+                }
+                self.tone_phases.resize(count, 0.0);
+                let mut sum = 0.0;
+                for (phase, &frequency) in self.tone_phases.iter_mut().zip(frequencies) {
+                    sum += (*phase * TAU).sin();
+                    *phase = (*phase + frequency as f64 / self.sample_rate as f64).fract();
+                }
+                (sum / count as f64) as f32
+            }
+        }
+    }
+}
+
+/// Polynomial band-limited step correction for a discontinuity at phase 0,
+/// which keeps square and saw waves from aliasing.
+fn poly_blep(t: f64, dt: f64) -> f64 {
+    if t < dt {
+        let x = t / dt;
+        2.0 * x - x * x - 1.0
+    } else if t > 1.0 - dt {
+        let x = (t - 1.0) / dt;
+        x * x + 2.0 * x + 1.0
+    } else {
+        0.0
+    }
+}
+
+impl AudioSource for GeneratorSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> usize {
+        self.channels
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> usize {
+        self.sync_settings();
+        let amplitude = self.current.amplitude();
+        let channels = self.channels;
+        let frames = out.len() / channels;
+        for frame in out.chunks_exact_mut(channels) {
+            let sample = self.next_sample() * amplitude;
+            frame.fill(sample);
+        }
+        frames * channels
+    }
+}
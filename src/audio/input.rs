@@ -0,0 +1,115 @@
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+
+use crate::AudioError;
+use crate::audio::AudioSource;
+use crate::audio::device::{StreamOptions, input_setup};
+use crate::audio::ring::SampleRing;
+
+/// Seconds of captured audio buffered before the oldest samples are dropped.
+const BUFFER_SECONDS: usize = 1;
+
+/// Live audio captured from an input device (microphone, line-in or a
+/// loopback source). Captured samples are queued until they are read.
+pub struct InputSource {
+    ring: Arc<SampleRing>,
+    errors: Receiver<AudioError>,
+    sample_rate: u32,
+    channels: usize,
+}
+
+impl InputSource {
+    /// Open `device` with its default input configuration and start capturing.
+    /// The returned stream must be kept alive for as long as capture should run.
+    pub fn open(device: &cpal::Device) -> Result<(Self, cpal::Stream), AudioError> {
+        Self::open_with_options(device, &StreamOptions::default())
+    }
+
+    /// Open `device` with the sample format and buffer size from `options`
+    /// and start capturing.
+    /// The returned stream must be kept alive for as long as capture should run.
+    pub fn open_with_options(
+        device: &cpal::Device,
+        options: &StreamOptions,
+    ) -> Result<(Self, cpal::Stream), AudioError> {
+        let setup = input_setup(device, options)?;
+        let sample_rate = setup.config.sample_rate.0;
+        let channels = setup.config.channels as usize;
+        let ring = Arc::new(SampleRing::new(
+            sample_rate as usize * channels * BUFFER_SECONDS,
+        ));
+        let (error_tx, errors) = mpsc::channel();
+
+        let stream_config = setup.config;
+        let (r, e) = (ring.clone(), error_tx);
+        let stream = match setup.sample_format {
+            cpal::SampleFormat::I8 => build::<i8>(device, &stream_config, r, e),
+            cpal::SampleFormat::I16 => build::<i16>(device, &stream_config, r, e),
+            cpal::SampleFormat::I32 => build::<i32>(device, &stream_config, r, e),
+            cpal::SampleFormat::U8 => build::<u8>(device, &stream_config, r, e),
+            cpal::SampleFormat::U16 => build::<u16>(device, &stream_config, r, e),
+            cpal::SampleFormat::U32 => build::<u32>(device, &stream_config, r, e),
+            cpal::SampleFormat::F32 => build::<f32>(device, &stream_config, r, e),
+            cpal::SampleFormat::F64 => build::<f64>(device, &stream_config, r, e),
+            format => Err(AudioError::UnsupportedSampleFormat(format)),
+        }?;
+        stream.play()?;
+
+        let source = InputSource {
+            ring,
+            errors,
+            sample_rate,
+            channels,
+        };
+        Ok((source, stream))
+    }
+}
+
+impl AudioSource for InputSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn channels(&self) -> usize {
+        self.channels
+    }
+
+    fn read(&mut self, out: &mut [f32]) -> usize {
+        self.ring.pop(out)
+    }
+
+    fn take_error(&mut self) -> Option<AudioError> {
+        self.errors.try_recv().ok()
+    }
+}
+
+fn build<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    ring: Arc<SampleRing>,
+    errors: Sender<AudioError>,
+) -> Result<cpal::Stream, AudioError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let mut samples: Vec<f32> = Vec::new();
+
+    let stream = device.build_input_stream(
+        config,
+        move |input: &[T], _: &cpal::InputCallbackInfo| {
+            samples.clear();
+            samples.extend(input.iter().map(|s| s.to_sample::<f32>()));
+            ring.push_overwrite(&samples);
+        },
+        move |err| {
+            let _ = errors.send(AudioError::Stream(err));
+        },
+        None,
+    )?;
+
+    Ok(stream)
+}
@@ -0,0 +1,51 @@
+pub mod device;
+pub mod engine;
+pub mod file;
+pub mod generator;
+pub mod input;
+pub mod player;
+pub mod resampler;
+mod ring;
+
+pub use device::StreamOptions;
+pub use engine::{AudioEngine, DeviceSelection, SourceSpec};
+pub use file::FileSource;
+pub use generator::{GeneratorSettings, GeneratorSource, Signal};
+pub use input::InputSource;
+pub use player::Player;
+pub use resampler::Resampler;
+
+use crate::AudioError;
+
+/// Pull-based source of interleaved audio samples.
+/// Implement this to feed custom audio into a `Player` and the visualizer.
+pub trait AudioSource: Send {
+    fn sample_rate(&self) -> u32;
+
+    fn channels(&self) -> usize;
+
+    /// Read up to `out.len()` interleaved samples and return how many were written.
+    /// `out.len()` is a multiple of `channels()` and the returned count must be too.
+    /// Returning fewer than requested means no more samples are available right now.
+    fn read(&mut self, out: &mut [f32]) -> usize;
+
+    /// Length in frames, if known.
+    fn duration(&self) -> Option<u64> {
+        None
+    }
+
+    /// Seek to `frame`. Returns false if the source cannot seek.
+    fn seek(&mut self, _frame: u64) -> bool {
+        false
+    }
+
+    /// True once a finite source has delivered all of its samples.
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    /// Take an error raised while producing samples, if any.
+    fn take_error(&mut self) -> Option<AudioError> {
+        None
+    }
+}
@@ -0,0 +1,261 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use cpal::traits::{DeviceTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+
+use crate::audio::AudioSource;
+use crate::audio::device::{StreamOptions, output_setup};
+use crate::{AudioError, ChannelSource, Transport, Visualizer};
+
+/// Interval at which `Player::analyze` pulls from its source.
+const ANALYZE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Drives an `AudioSource`: pulls samples, applies transport requests (pause,
+/// seek, loop, end of track) and feeds the visualizer. Playback stops when the
+/// player is dropped.
+pub struct Player {
+    _stream: Option<cpal::Stream>,
+    worker: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl Player {
+    /// Play `source` on `device`. The output stream is opened at the source's
+    /// sample rate with the device's default channel count.
+    pub fn play(
+        device: &cpal::Device,
+        source: Box<dyn AudioSource>,
+        visualizer: Arc<Mutex<Visualizer>>,
+        transport: Arc<Transport>,
+    ) -> Result<Self, AudioError> {
+        Self::play_with_options(
+            device,
+            &StreamOptions::default(),
+            source,
+            visualizer,
+            transport,
+        )
+    }
+
+    /// Play `source` on `device` with the sample format and buffer size from `options`.
+    pub fn play_with_options(
+        device: &cpal::Device,
+        options: &StreamOptions,
+        source: Box<dyn AudioSource>,
+        visualizer: Arc<Mutex<Visualizer>>,
+        transport: Arc<Transport>,
+    ) -> Result<Self, AudioError> {
+        let setup = output_setup(device, Some(source.sample_rate()), options)?;
+        let config = setup.config;
+        let pump = Pump::new(source, visualizer, transport);
+        let stream = match setup.sample_format {
+            cpal::SampleFormat::I8 => build::<i8>(device, &config, pump),
+            cpal::SampleFormat::I16 => build::<i16>(device, &config, pump),
+            cpal::SampleFormat::I32 => build::<i32>(device, &config, pump),
+            cpal::SampleFormat::U8 => build::<u8>(device, &config, pump),
+            cpal::SampleFormat::U16 => build::<u16>(device, &config, pump),
+            cpal::SampleFormat::U32 => build::<u32>(device, &config, pump),
+            cpal::SampleFormat::F32 => build::<f32>(device, &config, pump),
+            cpal::SampleFormat::F64 => build::<f64>(device, &config, pump),
+            format => Err(AudioError::UnsupportedSampleFormat(format)),
+        }?;
+        stream.play()?;
+
+        Ok(Self {
+            _stream: Some(stream),
+            worker: None,
+        })
+    }
+
+    /// Drive `source` in real time without audio output, e.g. for live input
+    /// that should not be monitored.
+    pub fn analyze(
+        source: Box<dyn AudioSource>,
+        visualizer: Arc<Mutex<Visualizer>>,
+        transport: Arc<Transport>,
+    ) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_worker = running.clone();
+        let sample_rate = source.sample_rate() as f64;
+        let channels = source.channels();
+        let mut pump = Pump::new(source, visualizer, transport);
+
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            let mut rendered = 0_u64;
+            let mut output: Vec<f32> = Vec::new();
+            while running_worker.load(Ordering::Relaxed) {
+                thread::sleep(ANALYZE_INTERVAL);
+                // Render as many frames as the elapsed time calls for
+                let due = (start.elapsed().as_secs_f64() * sample_rate) as u64;
+                let frames = (due - rendered) as usize;
+                rendered = due;
+                output.resize(frames * channels, 0.0);
+                pump.render(&mut output, channels);
+            }
+        });
+
+        Self {
+            _stream: None,
+            worker: Some((running, handle)),
+        }
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        if let Some((running, handle)) = self.worker.take() {
+            running.store(false, Ordering::Relaxed);
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Build an output stream that renders `pump` in format `T`.
+fn build<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut pump: Pump,
+) -> Result<cpal::Stream, AudioError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let channels = config.channels as usize;
+    let transport_err = pump.transport.clone();
+    let mut buffer: Vec<f32> = Vec::new();
+
+    let stream = device.build_output_stream(
+        config,
+        move |output: &mut [T], _: &cpal::OutputCallbackInfo| {
+            buffer.resize(output.len(), 0.0);
+            pump.render(&mut buffer, channels);
+            for (out, &sample) in output.iter_mut().zip(buffer.iter()) {
+                *out = T::from_sample(sample);
+            }
+        },
+        move |err| {
+            transport_err.report_error(AudioError::Stream(err));
+        },
+        None,
+    )?;
+
+    Ok(stream)
+}
+
+/// Moves samples from a source to an output buffer and the visualizer.
+struct Pump {
+    source: Box<dyn AudioSource>,
+    visualizer: Arc<Mutex<Visualizer>>,
+    transport: Arc<Transport>,
+    samples: Vec<f32>,
+    /// Analysis channels extracted from the last pull.
+    channels: [Vec<f32>; 2],
+}
+
+impl Pump {
+    fn new(
+        source: Box<dyn AudioSource>,
+        visualizer: Arc<Mutex<Visualizer>>,
+        transport: Arc<Transport>,
+    ) -> Self {
+        Self {
+            source,
+            visualizer,
+            transport,
+            samples: Vec::new(),
+            channels: Default::default(),
+        }
+    }
+
+    /// Fill interleaved `output` with `channels` channels.
+    fn render(&mut self, output: &mut [f32], channels: usize) {
+        let transport = &self.transport;
+        let source_channels = self.source.channels();
+
+        if let Some(frame) = transport.take_seek() {
+            self.source.seek(frame);
+        }
+        if let Some(err) = self.source.take_error() {
+            transport.report_error(err);
+        }
+
+        // Pull one source frame per output frame, padding with silence
+        // when paused, when the source falls behind or has ended
+        let frames = output.len() / channels;
+        self.samples.resize(frames * source_channels, 0.0);
+        let playing = transport.is_playing();
+        let mut read = 0;
+        while playing && read < self.samples.len() {
+            // Stop at the end of the loop region, so it wraps on its exact frame
+            let wanted = (self.samples.len() - read) / source_channels;
+            let frames = transport
+                .frames_to_loop_end()
+                .map_or(wanted, |left| wanted.min(left as usize));
+            let requested = frames * source_channels;
+            let n = self.source.read(&mut self.samples[read..read + requested]);
+            read += n;
+
+            // Jump back at the end of the loop region or the track
+            if let Some(frame) = transport.advance((n / source_channels) as u64) {
+                self.source.seek(frame);
+            } else if self.source.is_finished() {
+                self.source.seek(transport.end_of_track());
+                break;
+            } else if n < requested {
+                break;
+            }
+        }
+        self.samples[read..].fill(0.0);
+
+        // Write one frame at a time (respect channel count)
+        for (out_frame, in_frame) in output
+            .chunks_mut(channels)
+            .zip(self.samples.chunks(source_channels))
+        {
+            map_channels(in_frame, out_frame);
+        }
+
+        // For visualization: process FFT on current window
+        self.feed_visualizer(read);
+    }
+
+    /// Pass the configured analysis channel of the first `count` pulled
+    /// samples to the visualizer, or the left and right channels in stereo mode.
+    fn feed_visualizer(&mut self, count: usize) {
+        let samples = &self.samples[..count];
+        let channels = self.source.channels();
+        let [left, right] = &mut self.channels;
+        left.clear();
+        right.clear();
+
+        if let Ok(mut vis) = self.visualizer.lock() {
+            if vis.config.stereo && channels >= 2 {
+                ChannelSource::Left.extract(samples, channels, left);
+                ChannelSource::Right.extract(samples, channels, right);
+                vis.push_stereo_samples(left, right);
+            } else {
+                vis.config.channel_source.extract(samples, channels, left);
+                vis.push_samples(left);
+            }
+        }
+    }
+}
+
+/// Map one source frame onto one output frame.
+/// Mono is duplicated to every channel, a mono output gets the average of all
+/// channels; otherwise channels are copied in order and extra output channels
+/// stay silent.
+fn map_channels(input: &[f32], output: &mut [f32]) {
+    if input.len() == 1 {
+        output.fill(input[0]);
+    } else if output.len() == 1 {
+        output[0] = input.iter().sum::<f32>() / input.len() as f32;
+    } else {
+        output.fill(0.0);
+        for (o, &i) in output.iter_mut().zip(input) {
+            *o = i;
+        }
+    }
+}
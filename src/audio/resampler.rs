@@ -0,0 +1,197 @@
+use std::f64::consts::PI;
+
+/// Zero crossings of the sinc kernel on each side of the output sample.
+const HALF_TAPS: usize = 16;
+/// Number of fractional phases in the polyphase table.
+const PHASES: usize = 256;
+
+/// Streaming band-limited resampler for interleaved audio.
+/// Uses a Blackman-windowed sinc kernel stored as a polyphase table; values
+/// between two table phases are linearly interpolated. The cutoff follows the
+/// lower of the two Nyquist frequencies so downsampling does not alias.
+pub struct Resampler {
+    channels: usize,
+    /// Input frames advanced per output frame (input rate / output rate).
+    step: f64,
+    /// `PHASES + 1` rows of `2 * HALF_TAPS` coefficients.
+    table: Vec<f32>,
+    /// Interleaved input frames not yet fully consumed.
+    buffer: Vec<f32>,
+    /// Read position in frames, relative to the start of `buffer`.
+    position: f64,
+}
+
+impl Resampler {
+    pub fn new(input_rate: u32, output_rate: u32, channels: usize) -> Self {
+        let step = input_rate as f64 / output_rate as f64;
+        let mut resampler = Self {
+            channels: channels.max(1),
+            step,
+            table: Self::build_table(step),
+            buffer: Vec::new(),
+            position: 0.0,
+        };
+        resampler.reset();
+        resampler
+    }
+
+    /// True if input and output rates differ.
+    pub fn is_active(&self) -> bool {
+        self.step != 1.0
+    }
+
+    fn build_table(step: f64) -> Vec<f32> {
+        // Normalized cutoff relative to the input Nyquist, with a small guard band
+        let cutoff = (1.0 / step).min(1.0) * 0.95;
+        let taps = 2 * HALF_TAPS;
+        let mut table = Vec::with_capacity((PHASES + 1) * taps);
+        for phase in 0..=PHASES {
+            let frac = phase as f64 / PHASES as f64;
+            for k in 0..taps {
+                // Distance from the output sample to input tap k
+                let x = (k as f64 - HALF_TAPS as f64 + 1.0) - frac;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (PI * cutoff * x).sin() / (PI * cutoff * x)
+                };
+                // Blackman window over [-HALF_TAPS, HALF_TAPS]
+                let n = (x / HALF_TAPS as f64 + 1.0) / 2.0;
+                let window = if (0.0..=1.0).contains(&n) {
+                    0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos()
+                } else {
+                    0.0
+                };
+                table.push((cutoff * sinc * window) as f32);
+            }
+        }
+        table
+    }
+
+    /// Clear the internal history, e.g. after a seek.
+    pub fn reset(&mut self) {
+        // Prime with silence so the first output sample has full left context
+        self.buffer.clear();
+        self.buffer.resize((HALF_TAPS - 1) * self.channels, 0.0);
+        self.position = (HALF_TAPS - 1) as f64;
+    }
+
+    /// Resample interleaved `input` and append the result to `output`.
+    pub fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        if !self.is_active() {
+            output.extend_from_slice(input);
+            return;
+        }
+
+        let channels = self.channels;
+        let taps = 2 * HALF_TAPS;
+        self.buffer.extend_from_slice(input);
+        let frames = self.buffer.len() / channels;
+
+        // Produce output while the full kernel fits into the buffered frames
+        while (self.position as usize) + HALF_TAPS < frames {
+            let index = self.position as usize;
+            let phase = (self.position - index as f64) * PHASES as f64;
+            let p = (phase as usize).min(PHASES - 1);
+            let t = (phase - p as f64) as f32;
+            let row_a = &self.table[p * taps..(p + 1) * taps];
+            let row_b = &self.table[(p + 1) * taps..(p + 2) * taps];
+            let first = index + 1 - HALF_TAPS;
+
+            for ch in 0..channels {
+                let mut acc = 0.0_f32;
+                for k in 0..taps {
+                    let coeff = row_a[k] + (row_b[k] - row_a[k]) * t;
+                    acc += self.buffer[(first + k) * channels + ch] * coeff;
+                }
+                output.push(acc);
+            }
+            self.position += self.step;
+        }
+
+        // Drop frames no longer needed by the kernel
+        let keep_from = (self.position as usize + 1)
+            .saturating_sub(HALF_TAPS)
+            .min(frames);
+        self.buffer.drain(..keep_from * channels);
+        self.position -= keep_from as f64;
+    }
+
+    /// Resample the frames still waiting for right context at the end of the
+    /// stream, padded with silence, and start over.
+    pub fn flush(&mut self, output: &mut Vec<f32>) {
+        if self.is_active() {
+            let silence = vec![0.0; HALF_TAPS * self.channels];
+            self.process(&silence, output);
+        }
+        self.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f64, rate: u32, frames: usize, channels: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|i| {
+                let s = 0.5 * (2.0 * PI * frequency * i as f64 / rate as f64).sin();
+                std::iter::repeat_n(s as f32, channels)
+            })
+            .collect()
+    }
+
+    /// Resample `input` in chunks of `chunk` frames and flush.
+    fn resample(resampler: &mut Resampler, input: &[f32], chunk: usize) -> Vec<f32> {
+        let mut output = Vec::new();
+        for chunk in input.chunks(chunk * resampler.channels) {
+            resampler.process(chunk, &mut output);
+        }
+        resampler.flush(&mut output);
+        output
+    }
+
+    /// Amplitude of the `frequency` component of `samples`.
+    fn amplitude(samples: &[f32], frequency: f64, rate: u32) -> f64 {
+        let (mut re, mut im) = (0.0, 0.0);
+        for (i, &s) in samples.iter().enumerate() {
+            let phase = 2.0 * PI * frequency * i as f64 / rate as f64;
+            re += s as f64 * phase.cos();
+            im += s as f64 * phase.sin();
+        }
+        2.0 * (re * re + im * im).sqrt() / samples.len() as f64
+    }
+
+    #[test]
+    fn sine_keeps_frequency_and_amplitude() {
+        for (from, to) in [(44100, 48000), (48000, 44100), (96000, 48000)] {
+            let input = sine(1000.0, from, from as usize, 1);
+            let output = resample(&mut Resampler::new(from, to, 1), &input, 777);
+            // Skip the edges, where the kernel sees the padding
+            let middle = &output[1000..output.len() - 1000];
+            let level = amplitude(middle, 1000.0, to);
+            assert!((level - 0.5).abs() < 0.005, "{from} -> {to}: {level}");
+            let off_tone = amplitude(middle, 1010.0, to);
+            assert!(off_tone < 0.05, "{from} -> {to}: {off_tone}");
+        }
+    }
+
+    #[test]
+    fn flush_keeps_every_frame() {
+        let input = sine(440.0, 44100, 4410, 2);
+        let output = resample(&mut Resampler::new(44100, 48000, 2), &input, 100);
+        assert_eq!(output.len() % 2, 0);
+        let expected = 4410.0 * 48000.0 / 44100.0;
+        assert!((output.len() as f64 / 2.0 - expected).abs() <= 1.0);
+    }
+
+    #[test]
+    fn channels_stay_apart() {
+        let left = sine(500.0, 44100, 8820, 1);
+        let input: Vec<f32> = left.iter().flat_map(|&s| [s, 0.0]).collect();
+        let output = resample(&mut Resampler::new(44100, 48000, 2), &input, 512);
+        assert!(output.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+        let left: Vec<f32> = output.iter().step_by(2).copied().collect();
+        assert!((amplitude(&left[1000..8000], 500.0, 48000) - 0.5).abs() < 0.005);
+    }
+}
@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+struct RingState {
+    samples: VecDeque<f32>,
+    capacity: usize,
+    epoch: u64,
+    finished: bool,
+    closed: bool,
+}
+
+/// Bounded single-producer single-consumer sample queue.
+/// The producer blocks while the ring is full. The consumer never waits:
+/// while the producer holds the ring it reads nothing, an underrun, which
+/// keeps it safe to use from an audio callback.
+///
+/// Every `reset` starts a new epoch. Samples pushed for an older epoch are
+/// discarded, so data decoded before a seek never reaches playback.
+pub struct SampleRing {
+    state: Mutex<RingState>,
+    not_full: Condvar,
+}
+
+impl SampleRing {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            state: Mutex::new(RingState {
+                samples: VecDeque::with_capacity(capacity),
+                capacity,
+                epoch: 0,
+                finished: false,
+                closed: false,
+            }),
+            not_full: Condvar::new(),
+        }
+    }
+
+    /// Push all `samples` for `epoch` into the ring, waiting for free space as needed.
+    /// Returns false if the consumer has closed the ring.
+    pub fn push(&self, epoch: u64, mut samples: &[f32]) -> bool {
+        let mut state = self.state.lock().unwrap();
+        while !samples.is_empty() {
+            while state.samples.len() >= state.capacity && !state.closed && state.epoch == epoch {
+                state = self.not_full.wait(state).unwrap();
+            }
+            if state.closed {
+                return false;
+            }
+            if state.epoch != epoch {
+                // Stale data from before a reset
+                return true;
+            }
+            let free = state.capacity - state.samples.len();
+            let count = free.min(samples.len());
+            state.samples.extend(&samples[..count]);
+            samples = &samples[count..];
+        }
+        true
+    }
+
+    /// Push `samples` without waiting, dropping the oldest buffered samples
+    /// when the ring is full. For real-time producers such as capture callbacks.
+    pub fn push_overwrite(&self, samples: &[f32]) {
+        let mut state = self.state.lock().unwrap();
+        let samples = &samples[samples.len().saturating_sub(state.capacity)..];
+        let overflow = (state.samples.len() + samples.len()).saturating_sub(state.capacity);
+        state.samples.drain(..overflow);
+        state.samples.extend(samples);
+    }
+
+    /// Pop up to `out.len()` samples. Returns the number of samples written,
+    /// none while the producer holds the ring.
+    pub fn pop(&self, out: &mut [f32]) -> usize {
+        let Ok(mut state) = self.state.try_lock() else {
+            return 0;
+        };
+        let count = out.len().min(state.samples.len());
+        for (o, s) in out.iter_mut().zip(state.samples.drain(..count)) {
+            *o = s;
+        }
+        if count > 0 {
+            self.not_full.notify_one();
+        }
+        count
+    }
+
+    /// Mark the producer as done for `epoch`; no more samples will be pushed.
+    pub fn finish(&self, epoch: u64) {
+        let mut state = self.state.lock().unwrap();
+        if state.epoch == epoch {
+            state.finished = true;
+        }
+    }
+
+    /// True once the producer has finished and every sample was consumed.
+    /// False while the producer holds the ring.
+    pub fn is_drained(&self) -> bool {
+        self.state
+            .try_lock()
+            .is_ok_and(|state| state.finished && state.samples.is_empty())
+    }
+
+    /// Drop all buffered samples and start a new epoch. Returns the new epoch.
+    pub fn reset(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.samples.clear();
+        state.finished = false;
+        state.epoch += 1;
+        self.not_full.notify_all();
+        state.epoch
+    }
+
+    /// Close the ring from the consumer side, releasing a waiting producer.
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.not_full.notify_all();
+    }
+}
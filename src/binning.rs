@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::filters::BinLayout;
+
+/// How a bin that no FFT line falls into gets its value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinInterpolation {
+    /// Leave the bin empty.
+    #[default]
+    None,
+    /// Interpolate linearly between the neighbouring FFT lines in
+    /// log-frequency.
+    Linear,
+    /// Interpolate with a cubic Hermite spline through the four nearest FFT
+    /// lines in log-frequency.
+    Cubic,
+}
+
+impl BinInterpolation {
+    pub const ALL: [BinInterpolation; 3] = [
+        BinInterpolation::None,
+        BinInterpolation::Linear,
+        BinInterpolation::Cubic,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BinInterpolation::None => "None",
+            BinInterpolation::Linear => "Linear",
+            BinInterpolation::Cubic => "Cubic",
+        }
+    }
+
+    /// Magnitude at `freq` Hz from `lines` of (frequency, magnitude), sorted
+    /// by frequency. The DC line is ignored.
+    fn magnitude_at(&self, lines: &[(f32, f32)], freq: f32) -> f32 {
+        let lines = &lines[lines.partition_point(|&(f, _)| f <= 0.0)..];
+        let upper = lines.partition_point(|&(f, _)| f <= freq);
+        // Outside the lines, hold the nearest one
+        if upper == 0 {
+            return lines.first().map_or(0.0, |&(_, m)| m);
+        }
+        if upper == lines.len() {
+            return lines[upper - 1].1;
+        }
+        let point = |i: usize| {
+            let (f, m) = lines[i.min(lines.len() - 1)];
+            (f.ln(), m)
+        };
+        let (x0, y0) = point(upper - 1);
+        let (x1, y1) = point(upper);
+        let x = freq.ln();
+        let t = (x - x0) / (x1 - x0);
+        match self {
+            BinInterpolation::None => 0.0,
+            BinInterpolation::Linear => y0 + t * (y1 - y0),
+            BinInterpolation::Cubic => {
+                let slope = |(xa, ya): (f32, f32), (xb, yb): (f32, f32)| {
+                    if xb > xa { (yb - ya) / (xb - xa) } else { 0.0 }
+                };
+                let m0 = slope(point(upper.saturating_sub(2)), (x1, y1));
+                let m1 = slope((x0, y0), point(upper + 1));
+                let h = x1 - x0;
+                let (t2, t3) = (t * t, t * t * t);
+                let y = (2.0 * t3 - 3.0 * t2 + 1.0) * y0
+                    + (t3 - 2.0 * t2 + t) * h * m0
+                    + (-2.0 * t3 + 3.0 * t2) * y1
+                    + (t3 - t2) * h * m1;
+                y.max(0.0)
+            }
+        }
+    }
+}
+
+/// How the FFT lines within one bin are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinAggregation {
+    /// Total power in the band.
+    #[default]
+    Sum,
+    /// Mean magnitude.
+    Mean,
+    /// Largest magnitude.
+    Max,
+    /// Root mean square of the magnitudes.
+    Rms,
+}
+
+impl BinAggregation {
+    pub const ALL: [BinAggregation; 4] = [
+        BinAggregation::Sum,
+        BinAggregation::Mean,
+        BinAggregation::Max,
+        BinAggregation::Rms,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            BinAggregation::Sum => "Sum",
+            BinAggregation::Mean => "Mean",
+            BinAggregation::Max => "Max",
+            BinAggregation::Rms => "RMS",
+        }
+    }
+}
+
+/// Power of each bin of `layout` from FFT `lines` of (frequency, magnitude),
+/// sorted by frequency.
+pub fn bin_spectrum(
+    lines: &[(f32, f32)],
+    layout: &BinLayout,
+    interpolation: BinInterpolation,
+    aggregation: BinAggregation,
+) -> Vec<f32> {
+    let num_bins = layout.num_bins();
+    let mut bins = vec![0.0; num_bins];
+    let mut counts = vec![0_usize; num_bins];
+    for &(freq, mag) in lines {
+        let Some(bin) = layout.bin_of(freq) else {
+            continue;
+        };
+        match aggregation {
+            BinAggregation::Sum | BinAggregation::Rms => bins[bin] += mag * mag,
+            BinAggregation::Mean => bins[bin] += mag,
+            BinAggregation::Max => bins[bin] = f32::max(bins[bin], mag),
+        }
+        counts[bin] += 1;
+    }
+
+    for (bin, &count) in bins.iter_mut().zip(&counts) {
+        let count = count.max(1) as f32;
+        *bin = match aggregation {
+            BinAggregation::Sum => *bin,
+            BinAggregation::Mean => (*bin / count).powi(2),
+            BinAggregation::Max => bin.powi(2),
+            BinAggregation::Rms => *bin / count,
+        };
+    }
+
+    if interpolation == BinInterpolation::None || lines.len() < 2 {
+        return bins;
+    }
+    for (i, bin) in bins.iter_mut().enumerate() {
+        if counts[i] > 0 {
+            continue;
+        }
+        let center = layout.centers[i];
+        let power = interpolation.magnitude_at(lines, center).powi(2);
+        *bin = match aggregation {
+            // The fraction of a line's bandwidth the band covers
+            BinAggregation::Sum => {
+                power * (layout.edges[i + 1] - layout.edges[i]) / line_spacing(lines, center)
+            }
+            _ => power,
+        };
+    }
+    bins
+}
+
+/// Distance between the lines around `freq`; the spacing changes where FFTs
+/// of different lengths meet.
+fn line_spacing(lines: &[(f32, f32)], freq: f32) -> f32 {
+    let upper = lines
+        .partition_point(|&(f, _)| f <= freq)
+        .clamp(1, lines.len() - 1);
+    lines[upper].0 - lines[upper - 1].0
+}
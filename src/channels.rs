@@ -0,0 +1,69 @@
+/// Which part of a multichannel signal is fed to the analyzer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChannelSource {
+    /// Average of all channels.
+    #[default]
+    Mix,
+    Left,
+    Right,
+    /// (L + R) / 2
+    Mid,
+    /// (L - R) / 2
+    Side,
+    /// A specific channel index, e.g. for surround files.
+    Channel(usize),
+}
+
+impl ChannelSource {
+    pub fn label(&self) -> String {
+        match self {
+            ChannelSource::Mix => "Mix".to_string(),
+            ChannelSource::Left => "Left".to_string(),
+            ChannelSource::Right => "Right".to_string(),
+            ChannelSource::Mid => "Mid (L+R)".to_string(),
+            ChannelSource::Side => "Side (L−R)".to_string(),
+            ChannelSource::Channel(ch) => format!("Channel {}", ch + 1),
+        }
+    }
+
+    /// Sources available for a signal with `channels` channels.
+    pub fn options(channels: usize) -> Vec<ChannelSource> {
+        let mut options = vec![ChannelSource::Mix];
+        if channels >= 2 {
+            options.extend([
+                ChannelSource::Left,
+                ChannelSource::Right,
+                ChannelSource::Mid,
+                ChannelSource::Side,
+            ]);
+        }
+        if channels > 2 {
+            options.extend((0..channels).map(ChannelSource::Channel));
+        }
+        options
+    }
+
+    /// Sample for this source from one interleaved frame.
+    /// Mono frames always yield their single sample.
+    pub fn sample(&self, frame: &[f32]) -> f32 {
+        let channels = frame.len();
+        if channels < 2 {
+            return frame.first().copied().unwrap_or(0.0);
+        }
+        match *self {
+            ChannelSource::Mix => frame.iter().sum::<f32>() / channels as f32,
+            ChannelSource::Left => frame[0],
+            ChannelSource::Right => frame[1],
+            ChannelSource::Mid => (frame[0] + frame[1]) * 0.5,
+            ChannelSource::Side => (frame[0] - frame[1]) * 0.5,
+            ChannelSource::Channel(ch) => frame[ch.min(channels - 1)],
+        }
+    }
+
+    /// Extract this source from interleaved samples with `channels` channels,
+    /// appending one sample per frame to `out`.
+    pub fn extract(&self, interleaved: &[f32], channels: usize, out: &mut Vec<f32>) {
+        let channels = channels.max(1);
+        out.extend(interleaved.chunks_exact(channels).map(|f| self.sample(f)));
+    }
+}
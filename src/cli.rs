@@ -0,0 +1,249 @@
+use std::path::PathBuf;
+
+use clap::{Parser, ValueEnum};
+
+use visualizer::audio::device::{SAMPLE_FORMATS, find_host, parse_sample_format};
+use visualizer::filters::registry::{
+    find_spatial_factory, find_temporal_factory, spatial_factories, temporal_factories,
+};
+use visualizer::visualizer::{MAX_FFT_SIZE, MIN_WINDOW_SIZE};
+use visualizer::{FrontendKind, Signal};
+
+/// Real-time audio spectrum visualizer.
+#[derive(Parser, Debug)]
+#[command(version, about)]
+pub struct Cli {
+    /// Audio file to play (implies `--source file`).
+    pub file: Option<PathBuf>,
+
+    /// Where audio comes from. Defaults to `file` when a file is given,
+    /// otherwise to `live`.
+    #[arg(short, long, value_enum)]
+    pub source: Option<SourceKind>,
+
+    /// Preset file (TOML, or JSON with a `.json` extension) with analysis,
+    /// filter and color settings. Options given on the command line override it.
+    #[arg(long, value_name = "PATH")]
+    pub preset: Option<PathBuf>,
+
+    /// Analysis window size in samples [default: 2048].
+    #[arg(short, long, value_parser = parse_window_size)]
+    pub window_size: Option<usize>,
+
+    /// Pad each window with zeros to this many times the next power of two
+    /// (1, 2, 4, ...) for a finer frequency grid [default: 1].
+    #[arg(long, value_name = "FACTOR", value_parser = parse_zero_padding)]
+    pub zero_padding: Option<usize>,
+
+    /// Number of displayed frequency bins [default: 50].
+    #[arg(short, long, value_parser = clap::value_parser!(u16).range(1..=1024))]
+    pub bins: Option<u16>,
+
+    /// Lowest displayed frequency in Hz [default: 20].
+    #[arg(long, value_parser = parse_frequency)]
+    pub min_freq: Option<f32>,
+
+    /// Highest displayed frequency in Hz (defaults to Nyquist).
+    #[arg(long, value_parser = parse_frequency)]
+    pub max_freq: Option<f32>,
+
+    /// User interface to run.
+    #[arg(long, value_enum, default_value_t = FrontendArg::Egui)]
+    pub frontend: FrontendArg,
+
+    /// Audio host, e.g. `alsa` or `jack` (see `--list-devices`).
+    #[arg(long, value_name = "NAME", value_parser = parse_host)]
+    pub host: Option<cpal::HostId>,
+
+    /// List audio hosts and devices with their supported configurations, then exit.
+    #[arg(long)]
+    pub list_devices: bool,
+
+    /// Capture device for `--source live`, matched by (partial) name.
+    #[arg(long, value_name = "NAME")]
+    pub input_device: Option<String>,
+
+    /// Playback device, matched by (partial) name.
+    #[arg(long, value_name = "NAME")]
+    pub output_device: Option<String>,
+
+    /// Frames per audio callback (defaults to the driver's choice).
+    #[arg(long, value_name = "FRAMES", value_parser = clap::value_parser!(u32).range(16..=16384))]
+    pub buffer_size: Option<u32>,
+
+    /// Stream sample format, e.g. `f32` or `i16` (defaults to the device format).
+    #[arg(long, value_name = "FORMAT", value_parser = parse_format)]
+    pub sample_format: Option<cpal::SampleFormat>,
+
+    /// Test signal for `--source generator`: sine[:hz], sweep[:from:to:secs],
+    /// white, pink, brown, square[:hz], saw[:hz], impulse[:hz] or
+    /// multitone[:hz,hz,...].
+    #[arg(long, default_value = "sine:1000")]
+    pub signal: Signal,
+
+    /// Generator peak level in dBFS.
+    #[arg(long, default_value_t = -12.0, value_parser = parse_level, allow_negative_numbers = true)]
+    pub level: f32,
+
+    /// Generator sample rate in Hz (defaults to the output device rate);
+    /// the output device must support it.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1000..=384000))]
+    pub rate: Option<u32>,
+
+    /// Spatial filters in processing order, comma separated, or `none`
+    /// [possible values: gaussian, a-weighting].
+    #[arg(long, value_name = "FILTERS", value_parser = parse_spatial_chain)]
+    pub spatial: Option<FilterChain>,
+
+    /// Temporal filters in processing order, comma separated, or `none`
+    /// [possible values: attack-release, exponential, peak-hold-decay].
+    #[arg(long, value_name = "FILTERS", value_parser = parse_temporal_chain)]
+    pub temporal: Option<FilterChain>,
+}
+
+impl Cli {
+    /// Parse the command line, exiting with a usage message on invalid input.
+    pub fn parse_checked() -> Self {
+        let cli = Cli::parse();
+        if cli.source == Some(SourceKind::File) && cli.file.is_none() {
+            Self::fail("a file is required for `--source file`");
+        }
+        if cli.file.is_some() && cli.source.is_some_and(|s| s != SourceKind::File) {
+            Self::fail("a file can only be given with `--source file`");
+        }
+        if let Some(max_freq) = cli.max_freq
+            && max_freq <= cli.min_freq.unwrap_or(20.0)
+        {
+            Self::fail("`--max-freq` must be above `--min-freq`");
+        }
+        cli
+    }
+
+    /// Exit with `message` as a usage error.
+    pub fn fail(message: &str) -> ! {
+        use clap::CommandFactory;
+        Cli::command()
+            .error(clap::error::ErrorKind::ArgumentConflict, message)
+            .exit()
+    }
+
+    pub fn source_kind(&self) -> SourceKind {
+        match (self.source, &self.file) {
+            (Some(kind), _) => kind,
+            (None, Some(_)) => SourceKind::File,
+            (None, None) => SourceKind::Live,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SourceKind {
+    /// Decode and play an audio file.
+    File,
+    /// Capture from an input device.
+    Live,
+    /// Play a built-in test signal.
+    Generator,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FrontendArg {
+    Egui,
+}
+
+impl From<FrontendArg> for FrontendKind {
+    fn from(arg: FrontendArg) -> Self {
+        match arg {
+            FrontendArg::Egui => FrontendKind::Egui,
+        }
+    }
+}
+
+/// Ordered filter registry keys.
+#[derive(Clone, Debug)]
+pub struct FilterChain(pub Vec<String>);
+
+fn parse_window_size(s: &str) -> Result<usize, String> {
+    let size: usize = s.parse().map_err(|_| format!("'{}' is not a number", s))?;
+    if !(MIN_WINDOW_SIZE..=MAX_FFT_SIZE).contains(&size) {
+        return Err(format!(
+            "{} is not between {} and {}",
+            size, MIN_WINDOW_SIZE, MAX_FFT_SIZE
+        ));
+    }
+    Ok(size)
+}
+
+fn parse_zero_padding(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(factor) if factor.is_power_of_two() && factor <= 16 => Ok(factor),
+        _ => Err(format!("'{}' is not one of 1, 2, 4, 8 or 16", s)),
+    }
+}
+
+fn parse_host(s: &str) -> Result<cpal::HostId, String> {
+    find_host(s).map_err(|_| {
+        let hosts: Vec<&str> = cpal::available_hosts().iter().map(|id| id.name()).collect();
+        format!("unknown host '{}' (available: {})", s, hosts.join(", "))
+    })
+}
+
+fn parse_format(s: &str) -> Result<cpal::SampleFormat, String> {
+    parse_sample_format(s).ok_or_else(|| {
+        let formats: Vec<String> = SAMPLE_FORMATS.iter().map(|f| f.to_string()).collect();
+        format!(
+            "unknown sample format '{}' (expected {})",
+            s,
+            formats.join(", ")
+        )
+    })
+}
+
+fn parse_frequency(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(freq) if freq.is_finite() && freq > 0.0 => Ok(freq),
+        _ => Err(format!("'{}' is not a positive frequency", s)),
+    }
+}
+
+fn parse_level(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(level) if level.is_finite() && level <= 0.0 => Ok(level),
+        _ => Err(format!("'{}' is not a level at or below 0 dBFS", s)),
+    }
+}
+
+fn parse_chain(
+    s: &str,
+    known: impl Fn(&str) -> bool,
+    keys: Vec<&str>,
+) -> Result<FilterChain, String> {
+    if s == "none" {
+        return Ok(FilterChain(Vec::new()));
+    }
+    let mut chain = Vec::new();
+    for key in s.split(',').map(str::trim) {
+        if !known(key) {
+            return Err(format!(
+                "unknown filter '{}' (expected {})",
+                key,
+                keys.join(", ")
+            ));
+        }
+        if chain.iter().any(|k| k == key) {
+            return Err(format!("filter '{}' is listed twice", key));
+        }
+        chain.push(key.to_string());
+    }
+    Ok(FilterChain(chain))
+}
+
+fn parse_spatial_chain(s: &str) -> Result<FilterChain, String> {
+    let keys = spatial_factories().iter().map(|f| f.key).collect();
+    parse_chain(s, |key| find_spatial_factory(key).is_some(), keys)
+}
+
+fn parse_temporal_chain(s: &str) -> Result<FilterChain, String> {
+    let keys = temporal_factories().iter().map(|f| f.key).collect();
+    parse_chain(s, |key| find_temporal_factory(key).is_some(), keys)
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+
+use symphonia::core::errors::Error as SymphoniaError;
+
+use crate::analysis::{MAX_BINS_PER_OCTAVE, MAX_Q};
+use crate::spectrogram::MAX_SPECTROGRAM_DEPTH;
+use crate::visualizer::{MAX_BINS, MAX_FFT_SIZE, MAX_OVERLAP, MIN_WINDOW_SIZE};
+
+/// Errors raised while loading audio or setting up audio streams.
+#[derive(Debug)]
+pub enum AudioError {
+    /// The media file could not be opened.
+    Open(std::io::Error),
+    /// No format reader recognized the file.
+    UnsupportedFormat(SymphoniaError),
+    /// The file has no track with a decodeable codec.
+    NoSupportedTrack,
+    /// No decoder is available for the track's codec.
+    UnsupportedCodec(SymphoniaError),
+    MissingSampleRate,
+    MissingChannelLayout,
+    /// An unrecoverable error while reading or decoding packets.
+    Decode(SymphoniaError),
+    NoOutputDevice,
+    NoInputDevice,
+    /// No audio host matches the requested name.
+    HostNotFound(String),
+    HostUnavailable(cpal::HostUnavailable),
+    /// No device matches the requested name.
+    DeviceNotFound(String),
+    /// Devices could not be enumerated.
+    Devices(cpal::DevicesError),
+    DeviceConfig(cpal::DefaultStreamConfigError),
+    UnsupportedSampleFormat(cpal::SampleFormat),
+    /// The device does not support the requested sample rate in Hz.
+    UnsupportedSampleRate(u32),
+    BuildStream(cpal::BuildStreamError),
+    PlayStream(cpal::PlayStreamError),
+    /// An error reported by a running stream.
+    Stream(cpal::StreamError),
+}
+
+impl fmt::Display for AudioError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AudioError::Open(err) => write!(f, "failed to open media: {}", err),
+            AudioError::UnsupportedFormat(err) => write!(f, "unsupported format: {}", err),
+            AudioError::NoSupportedTrack => write!(f, "no supported audio tracks"),
+            AudioError::UnsupportedCodec(err) => write!(f, "unsupported codec: {}", err),
+            AudioError::MissingSampleRate => write!(f, "track has no sample rate"),
+            AudioError::MissingChannelLayout => write!(f, "track has no channel layout"),
+            AudioError::Decode(err) => write!(f, "decoding failed: {}", err),
+            AudioError::NoOutputDevice => write!(f, "no output device available"),
+            AudioError::NoInputDevice => write!(f, "no input device available"),
+            AudioError::HostNotFound(name) => write!(f, "no audio host named '{}'", name),
+            AudioError::HostUnavailable(err) => write!(f, "audio host unavailable: {}", err),
+            AudioError::DeviceNotFound(name) => write!(f, "no audio device matches '{}'", name),
+            AudioError::Devices(err) => write!(f, "failed to list audio devices: {}", err),
+            AudioError::DeviceConfig(err) => write!(f, "device configuration error: {}", err),
+            AudioError::UnsupportedSampleFormat(format) => {
+                write!(f, "unsupported sample format: {}", format)
+            }
+            AudioError::UnsupportedSampleRate(rate) => {
+                write!(
+                    f,
+                    "the device does not support a sample rate of {} Hz",
+                    rate
+                )
+            }
+            AudioError::BuildStream(err) => write!(f, "failed to build stream: {}", err),
+            AudioError::PlayStream(err) => write!(f, "failed to start stream: {}", err),
+            AudioError::Stream(err) => write!(f, "stream error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+impl From<cpal::HostUnavailable> for AudioError {
+    fn from(err: cpal::HostUnavailable) -> Self {
+        AudioError::HostUnavailable(err)
+    }
+}
+
+impl From<cpal::DevicesError> for AudioError {
+    fn from(err: cpal::DevicesError) -> Self {
+        AudioError::Devices(err)
+    }
+}
+
+impl From<cpal::DefaultStreamConfigError> for AudioError {
+    fn from(err: cpal::DefaultStreamConfigError) -> Self {
+        AudioError::DeviceConfig(err)
+    }
+}
+
+impl From<cpal::BuildStreamError> for AudioError {
+    fn from(err: cpal::BuildStreamError) -> Self {
+        AudioError::BuildStream(err)
+    }
+}
+
+impl From<cpal::PlayStreamError> for AudioError {
+    fn from(err: cpal::PlayStreamError) -> Self {
+        AudioError::PlayStream(err)
+    }
+}
+
+impl From<cpal::StreamError> for AudioError {
+    fn from(err: cpal::StreamError) -> Self {
+        AudioError::Stream(err)
+    }
+}
+
+/// Invalid settings or input passed to the visualizer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigError {
+    /// The window is shorter than `MIN_WINDOW_SIZE`, or its padded FFT is
+    /// larger than `MAX_FFT_SIZE`.
+    WindowSize {
+        window_size: usize,
+        zero_padding: usize,
+    },
+    /// The zero-padding factor is not a power of two.
+    ZeroPadding(usize),
+    NumBins(usize),
+    FrequencyRange(f32, f32),
+    Overlap(f32),
+    HopSize(usize),
+    /// Spectrogram depth in frames out of range.
+    SpectrogramDepth(usize),
+    /// Constant-Q bins per octave or Q factor out of range.
+    ConstantQ {
+        bins_per_octave: u32,
+        q: f32,
+    },
+    /// Multi-resolution windows not ordered within the supported sizes, or
+    /// crossovers not ascending.
+    MultiResolution {
+        bass_window: usize,
+        mid_window: usize,
+        treble_window: usize,
+        low_crossover: f32,
+        high_crossover: f32,
+    },
+    /// Fewer samples than one window were given.
+    NotEnoughSamples {
+        needed: usize,
+        got: usize,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::WindowSize {
+                window_size,
+                zero_padding,
+            } => write!(
+                f,
+                "window size {} with {}x zero padding is not between {} samples and a {}-point FFT",
+                window_size, zero_padding, MIN_WINDOW_SIZE, MAX_FFT_SIZE
+            ),
+            ConfigError::ZeroPadding(factor) => {
+                write!(f, "zero padding {}x is not a power of two", factor)
+            }
+            ConfigError::NumBins(bins) => {
+                write!(f, "{} bins is not between 1 and {}", bins, MAX_BINS)
+            }
+            ConfigError::FrequencyRange(min, max) => {
+                write!(f, "invalid frequency range {} - {} Hz", min, max)
+            }
+            ConfigError::Overlap(overlap) => write!(
+                f,
+                "overlap {} is not between 0 and {}",
+                overlap, MAX_OVERLAP
+            ),
+            ConfigError::HopSize(hop) => write!(f, "invalid hop size {}", hop),
+            ConfigError::SpectrogramDepth(depth) => write!(
+                f,
+                "spectrogram depth {} is not between 1 and {} frames",
+                depth, MAX_SPECTROGRAM_DEPTH
+            ),
+            ConfigError::ConstantQ { bins_per_octave, q } => write!(
+                f,
+                "constant-Q needs 1 to {} bins per octave and a Q factor from 1 to {}, got {} and {}",
+                MAX_BINS_PER_OCTAVE, MAX_Q, bins_per_octave, q
+            ),
+            ConfigError::MultiResolution {
+                bass_window,
+                mid_window,
+                treble_window,
+                low_crossover,
+                high_crossover,
+            } => write!(
+                f,
+                "multi-resolution needs {} <= treble window ({}) <= mid window ({}) <= \
+                 bass window ({}) <= {} and 0 < low crossover ({} Hz) < high crossover ({} Hz)",
+                MIN_WINDOW_SIZE,
+                treble_window,
+                mid_window,
+                bass_window,
+                MAX_FFT_SIZE,
+                low_crossover,
+                high_crossover
+            ),
+            ConfigError::NotEnoughSamples { needed, got } => {
+                write!(f, "{} samples given, a window needs {}", got, needed)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Errors raised while loading or saving a preset.
+#[derive(Debug)]
+pub enum PresetError {
+    /// The preset file could not be read or written.
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    TomlSerialize(toml::ser::Error),
+    Json(serde_json::Error),
+    /// The preset parsed but holds values the visualizer cannot use.
+    Invalid(String),
+    /// The visualizer rejected a value of the preset.
+    Config(ConfigError),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::Io(err) => write!(f, "preset file error: {}", err),
+            PresetError::Toml(err) => write!(f, "invalid preset: {}", err),
+            PresetError::TomlSerialize(err) => write!(f, "failed to write preset: {}", err),
+            PresetError::Json(err) => write!(f, "invalid preset: {}", err),
+            PresetError::Invalid(message) => write!(f, "invalid preset: {}", message),
+            PresetError::Config(err) => write!(f, "invalid preset: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PresetError {}
+
+impl From<std::io::Error> for PresetError {
+    fn from(err: std::io::Error) -> Self {
+        PresetError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for PresetError {
+    fn from(err: toml::de::Error) -> Self {
+        PresetError::Toml(err)
+    }
+}
+
+impl From<toml::ser::Error> for PresetError {
+    fn from(err: toml::ser::Error) -> Self {
+        PresetError::TomlSerialize(err)
+    }
+}
+
+impl From<serde_json::Error> for PresetError {
+    fn from(err: serde_json::Error) -> Self {
+        PresetError::Json(err)
+    }
+}
+
+impl From<ConfigError> for PresetError {
+    fn from(err: ConfigError) -> Self {
+        PresetError::Config(err)
+    }
+}
@@ -1,5 +1,9 @@
-use crate::filters::{BinLayout, SpatialFilter, TemporalFilter};
+use crate::filters::registry::{SpatialFactory, TemporalFactory};
+use crate::filters::{
+    BinLayout, SpatialFilter, SpatialFilterPreset, TemporalFilter, TemporalFilterPreset,
+};
 use std::any::TypeId;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 pub struct FilterEntry<T: ?Sized> {
@@ -17,6 +21,9 @@ impl<T: ?Sized> FilterEntry<T> {
 pub struct FilterManager {
     spatial_filters: Vec<FilterEntry<dyn SpatialFilter>>,
     temporal_filters: Vec<FilterEntry<dyn TemporalFilter>>,
+    /// Temporal filter states of the right channel in stereo mode, by entry
+    /// id. The filters hold the state of the left or mono channel themselves.
+    right_states: Mutex<HashMap<usize, Vec<f32>>>,
     next_id: usize,
     layout: BinLayout,
 }
@@ -26,6 +33,7 @@ impl FilterManager {
         FilterManager {
             spatial_filters: Vec::new(),
             temporal_filters: Vec::new(),
+            right_states: Mutex::default(),
             next_id: 0,
             layout,
         }
@@ -45,17 +53,33 @@ impl FilterManager {
         &self.temporal_filters
     }
 
+    /// Parameters of the spatial chain in processing order.
+    pub fn spatial_presets(&self) -> Vec<SpatialFilterPreset> {
+        self.spatial_filters
+            .iter()
+            .filter_map(|entry| entry.try_lock().map(|f| f.preset()))
+            .collect()
+    }
+
+    /// Parameters of the temporal chain in processing order.
+    pub fn temporal_presets(&self) -> Vec<TemporalFilterPreset> {
+        self.temporal_filters
+            .iter()
+            .filter_map(|entry| entry.try_lock().map(|f| f.preset()))
+            .collect()
+    }
+
     pub fn active_spatial_filters_types(&self) -> Vec<TypeId> {
         self.spatial_filters
             .iter()
-            .filter_map(|entry| Some(entry.type_id))
+            .map(|entry| entry.type_id)
             .collect()
     }
 
     pub fn active_temporal_filters_types(&self) -> Vec<TypeId> {
         self.temporal_filters
             .iter()
-            .filter_map(|entry| Some(entry.type_id))
+            .map(|entry| entry.type_id)
             .collect()
     }
 
@@ -87,6 +111,23 @@ impl FilterManager {
         id
     }
 
+    /// Add a default-configured filter from the registry.
+    pub fn add_spatial_from_factory(&mut self, factory: &SpatialFactory) -> usize {
+        let id = self.gen_id();
+        self.spatial_filters.push(FilterEntry {
+            id,
+            type_id: factory.type_id,
+            filter: (factory.make)(),
+        });
+        self.refresh_layout();
+
+        id
+    }
+
+    pub fn clear_spatial_filters(&mut self) {
+        self.spatial_filters.clear();
+    }
+
     pub fn remove_spatial_filter(&mut self, id: usize) {
         if let Some(idx) = self.spatial_filters.iter().position(|e| e.id == id) {
             self.spatial_filters.remove(idx);
@@ -116,6 +157,23 @@ impl FilterManager {
         id
     }
 
+    /// Add a default-configured filter from the registry.
+    pub fn add_temporal_from_factory(&mut self, factory: &TemporalFactory) -> usize {
+        let id = self.gen_id();
+        self.temporal_filters.push(FilterEntry {
+            id,
+            type_id: factory.type_id,
+            filter: (factory.make)(),
+        });
+
+        id
+    }
+
+    pub fn clear_temporal_filters(&mut self) {
+        self.temporal_filters.clear();
+        self.right_states_mut().clear();
+    }
+
     pub fn remove_temporal_filter(&mut self, id: usize) {
         if let Some(idx) = self
             .temporal_filters
@@ -123,6 +181,7 @@ impl FilterManager {
             .position(|entry| entry.id == id)
         {
             self.temporal_filters.remove(idx);
+            self.right_states_mut().remove(&id);
         }
     }
 
@@ -154,6 +213,33 @@ impl FilterManager {
         }
     }
 
+    /// Like `apply_temporal_filters` for the right channel in stereo mode,
+    /// with its own filter states.
+    pub fn apply_right_temporal_filters(&self, samples: &mut [f32]) {
+        let Ok(mut right_states) = self.right_states.lock() else {
+            return;
+        };
+        for entry in &self.temporal_filters {
+            if let Ok(mut filter) = entry.filter.lock() {
+                // Swap the right channel's state in for the call
+                let state = right_states.entry(entry.id).or_default();
+                if let Some(own) = filter.state_vec() {
+                    std::mem::swap(own, state);
+                }
+                filter.process(samples);
+                if let Some(own) = filter.state_vec() {
+                    std::mem::swap(own, state);
+                }
+            }
+        }
+    }
+
+    fn right_states_mut(&mut self) -> &mut HashMap<usize, Vec<f32>> {
+        self.right_states
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
     pub fn update_layout(&mut self, layout: BinLayout) {
         self.layout = layout;
         self.refresh_layout();
@@ -167,11 +253,47 @@ impl FilterManager {
         }
     }
 
+    /// Copy the state of each temporal filter in chain order, with its type.
+    pub fn temporal_states(&self) -> Vec<(TypeId, Vec<f32>)> {
+        self.temporal_filters
+            .iter()
+            .filter_map(|entry| {
+                let mut filter = entry.try_lock()?;
+                let state = filter.state_vec()?.clone();
+                Some((entry.type_id, state))
+            })
+            .collect()
+    }
+
+    /// Hand states from `temporal_states` back to the chain. Each filter takes
+    /// the first unused state of its type, so a rebuilt chain keeps its history
+    /// as long as the filter types match. States that do not match the number
+    /// of bins of the layout are skipped.
+    pub fn restore_temporal_states(&self, mut states: Vec<(TypeId, Vec<f32>)>) {
+        for entry in &self.temporal_filters {
+            let Some(pos) = states.iter().position(|(tid, _)| *tid == entry.type_id) else {
+                continue;
+            };
+            let (_, state) = states.remove(pos);
+            if state.len() != self.layout.num_bins() {
+                continue;
+            }
+            if let Some(mut filter) = entry.try_lock()
+                && let Some(prev) = filter.state_vec()
+            {
+                *prev = state;
+            }
+        }
+    }
+
     pub fn reset_temporal_filters(&self) {
         for entry in &self.temporal_filters {
             if let Ok(mut filter) = entry.filter.lock() {
                 filter.reset();
             }
         }
+        if let Ok(mut right_states) = self.right_states.lock() {
+            right_states.values_mut().for_each(|state| state.fill(0.0));
+        }
     }
 }
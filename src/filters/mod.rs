@@ -1,8 +1,10 @@
 pub mod manager;
+pub mod preset;
 pub mod registry;
 pub mod spatial;
 pub mod temporal;
 
 pub use manager::FilterManager;
+pub use preset::{SpatialFilterPreset, TemporalFilterPreset};
 pub use spatial::{AWeightingFilter, BinLayout, GaussianFilter, SpatialFilter};
 pub use temporal::{AttackReleaseFilter, ExponentialFilter, PeakHoldDecayFilter, TemporalFilter};
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::filters::{
+    AWeightingFilter, AttackReleaseFilter, ExponentialFilter, FilterManager, GaussianFilter,
+    PeakHoldDecayFilter,
+};
+
+/// Serializable description of a spatial filter and its parameters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SpatialFilterPreset {
+    Gaussian {
+        sigma: f32,
+        radius: usize,
+        passes: usize,
+        #[serde(default = "enabled")]
+        enabled: bool,
+    },
+    AWeighting {
+        #[serde(default = "enabled")]
+        enabled: bool,
+    },
+}
+
+/// Serializable description of a temporal filter and its parameters.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum TemporalFilterPreset {
+    AttackRelease {
+        attack: f32,
+        release: f32,
+        #[serde(default = "enabled")]
+        enabled: bool,
+    },
+    Exponential {
+        alpha: f32,
+    },
+    PeakHoldDecay {
+        decay: f32,
+    },
+}
+
+fn enabled() -> bool {
+    true
+}
+
+impl SpatialFilterPreset {
+    /// Append the described filter to the spatial chain of `manager`.
+    pub fn add_to(&self, manager: &mut FilterManager) -> usize {
+        match *self {
+            SpatialFilterPreset::Gaussian {
+                sigma,
+                radius,
+                passes,
+                enabled,
+            } => {
+                let mut filter = GaussianFilter::new(sigma, radius, passes);
+                filter.enabled = enabled;
+                manager.add_spatial_filter(filter)
+            }
+            SpatialFilterPreset::AWeighting { enabled } => {
+                let mut filter = AWeightingFilter::default();
+                filter.enabled = enabled;
+                manager.add_spatial_filter(filter)
+            }
+        }
+    }
+
+    /// Check that the parameters are in the range the filter supports.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            SpatialFilterPreset::Gaussian {
+                sigma,
+                radius,
+                passes,
+                ..
+            } => {
+                if !(sigma.is_finite() && sigma > 0.0) {
+                    return Err(format!("gaussian sigma must be positive, got {}", sigma));
+                }
+                if radius > 128 {
+                    return Err(format!(
+                        "gaussian radius must be at most 128, got {}",
+                        radius
+                    ));
+                }
+                if !(1..=20).contains(&passes) {
+                    return Err(format!(
+                        "gaussian passes must be between 1 and 20, got {}",
+                        passes
+                    ));
+                }
+                Ok(())
+            }
+            SpatialFilterPreset::AWeighting { .. } => Ok(()),
+        }
+    }
+}
+
+impl TemporalFilterPreset {
+    /// Append the described filter to the temporal chain of `manager`.
+    pub fn add_to(&self, manager: &mut FilterManager) -> usize {
+        match *self {
+            TemporalFilterPreset::AttackRelease {
+                attack,
+                release,
+                enabled,
+            } => {
+                let mut filter = AttackReleaseFilter::new(attack, release);
+                filter.enabled = enabled;
+                manager.add_temporal_filter(filter)
+            }
+            TemporalFilterPreset::Exponential { alpha } => {
+                manager.add_temporal_filter(ExponentialFilter::new(alpha))
+            }
+            TemporalFilterPreset::PeakHoldDecay { decay } => {
+                manager.add_temporal_filter(PeakHoldDecayFilter::new(decay))
+            }
+        }
+    }
+
+    /// Check that the smoothing factors lie in 0..=1.
+    pub fn validate(&self) -> Result<(), String> {
+        let factors: &[(&str, f32)] = match self {
+            TemporalFilterPreset::AttackRelease {
+                attack, release, ..
+            } => &[("attack", *attack), ("release", *release)],
+            TemporalFilterPreset::Exponential { alpha } => &[("alpha", *alpha)],
+            TemporalFilterPreset::PeakHoldDecay { decay } => &[("decay", *decay)],
+        };
+        for &(name, value) in factors {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,24 +1,70 @@
-use crate::filters::{AWeightingFilter, GaussianFilter, SpatialFilter};
+use crate::filters::{
+    AWeightingFilter, AttackReleaseFilter, ExponentialFilter, GaussianFilter, PeakHoldDecayFilter,
+    SpatialFilter, TemporalFilter,
+};
 use std::any::TypeId;
 use std::sync::{Arc, Mutex};
 
 pub struct SpatialFactory {
     pub type_id: TypeId,
     pub name: &'static str,
+    /// Identifier used on the command line.
+    pub key: &'static str,
     pub make: fn() -> Arc<Mutex<dyn SpatialFilter>>,
 }
 
+pub struct TemporalFactory {
+    pub type_id: TypeId,
+    pub name: &'static str,
+    /// Identifier used on the command line.
+    pub key: &'static str,
+    pub make: fn() -> Arc<Mutex<dyn TemporalFilter>>,
+}
+
 pub fn spatial_factories() -> Vec<SpatialFactory> {
     vec![
         SpatialFactory {
             type_id: TypeId::of::<GaussianFilter>(),
             name: "Gaussian",
+            key: "gaussian",
             make: || Arc::new(Mutex::new(GaussianFilter::default())),
         },
         SpatialFactory {
             type_id: TypeId::of::<AWeightingFilter>(),
             name: "A-Weighting",
+            key: "a-weighting",
             make: || Arc::new(Mutex::new(AWeightingFilter::default())),
         },
     ]
 }
+
+pub fn temporal_factories() -> Vec<TemporalFactory> {
+    vec![
+        TemporalFactory {
+            type_id: TypeId::of::<AttackReleaseFilter>(),
+            name: "Attack/Release",
+            key: "attack-release",
+            make: || Arc::new(Mutex::new(AttackReleaseFilter::new(0.7, 0.9))),
+        },
+        TemporalFactory {
+            type_id: TypeId::of::<ExponentialFilter>(),
+            name: "Exponential",
+            key: "exponential",
+            make: || Arc::new(Mutex::new(ExponentialFilter::new(0.5))),
+        },
+        TemporalFactory {
+            type_id: TypeId::of::<PeakHoldDecayFilter>(),
+            name: "Peak-Hold & Decay",
+            key: "peak-hold-decay",
+            make: || Arc::new(Mutex::new(PeakHoldDecayFilter::new(0.9))),
+        },
+    ]
+}
+
+pub fn find_spatial_factory(key: &str) -> Option<SpatialFactory> {
+    spatial_factories().into_iter().find(|f| f.key == key)
+}
+
+pub fn find_temporal_factory(key: &str) -> Option<TemporalFactory> {
+    temporal_factories().into_iter().find(|f| f.key == key)
+}
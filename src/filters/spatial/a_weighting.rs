@@ -1,4 +1,4 @@
-use crate::filters::{BinLayout, SpatialFilter};
+use crate::filters::{BinLayout, SpatialFilter, SpatialFilterPreset};
 
 const C1: f32 = 20.6_f32 * 20.6_f32;
 const C2: f32 = 107.7_f32 * 107.7_f32;
@@ -17,14 +17,16 @@ pub struct AWeightingFilter {
     pub enabled: bool,
 }
 
-impl AWeightingFilter {
-    pub fn default() -> Self {
+impl Default for AWeightingFilter {
+    fn default() -> Self {
         Self {
             weights: Vec::new(),
             enabled: true,
         }
     }
+}
 
+impl AWeightingFilter {
     #[inline]
     fn compute_weight(f: f32) -> f32 {
         if f <= 0.0 {
@@ -57,4 +59,10 @@ impl SpatialFilter for AWeightingFilter {
             }
         }
     }
+
+    fn preset(&self) -> SpatialFilterPreset {
+        SpatialFilterPreset::AWeighting {
+            enabled: self.enabled,
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use crate::filters::SpatialFilter;
+use crate::filters::{SpatialFilter, SpatialFilterPreset};
 
 /// Gaussian spatial filter.
 /// Applies a Gaussian blur to the input samples.
@@ -13,6 +13,15 @@ pub struct GaussianFilter {
     pub enabled: bool,
 }
 
+impl Default for GaussianFilter {
+    fn default() -> Self {
+        let sigma = 2.0;
+        let radius = 4;
+        let num_passes = 3;
+        Self::new(sigma, radius, num_passes)
+    }
+}
+
 impl GaussianFilter {
     pub fn new(sigma: f32, radius: usize, num_passes: usize) -> Self {
         Self {
@@ -24,13 +33,6 @@ impl GaussianFilter {
         }
     }
 
-    pub fn default() -> Self {
-        let sigma = 2.0;
-        let radius = 4;
-        let num_passes = 3;
-        Self::new(sigma, radius, num_passes)
-    }
-
     fn compute_kernel(sigma: f32, radius: usize) -> Vec<f32> {
         let mut kernel = Vec::with_capacity(2 * radius + 1);
         let denom = 2.0 * sigma * sigma;
@@ -54,7 +56,7 @@ impl GaussianFilter {
 
         let num_samples = samples.len();
 
-        for i in 0..num_samples {
+        for (i, o) in out.iter_mut().enumerate() {
             let mut acc = 0.0;
             for (k, &weight) in self.kernel.iter().enumerate() {
                 let sample_index = i as isize + k as isize - self.radius as isize;
@@ -62,7 +64,7 @@ impl GaussianFilter {
                     acc += samples[sample_index as usize] * weight;
                 }
             }
-            out[i] = acc;
+            *o = acc;
         }
 
         samples.copy_from_slice(&out);
@@ -85,4 +87,13 @@ impl SpatialFilter for GaussianFilter {
             }
         }
     }
+
+    fn preset(&self) -> SpatialFilterPreset {
+        SpatialFilterPreset::Gaussian {
+            sigma: self.sigma,
+            radius: self.radius,
+            passes: self.num_passes,
+            enabled: self.enabled,
+        }
+    }
 }
@@ -7,44 +7,64 @@ pub use gaussian::GaussianFilter;
 
 use std::any::TypeId;
 
+use crate::filters::SpatialFilterPreset;
 use crate::frontend::egui_frontend::UiComponent;
+use crate::scale::FrequencyScale;
 
 // Bin layout info passed to filters needing bin center frequencies.
 #[derive(Clone)]
 pub struct BinLayout {
     pub centers: Vec<f32>,
+    /// Band edges in Hz, one more than the number of bins.
+    pub edges: Vec<f32>,
     pub min_freq: f32,
     pub max_freq: f32,
-    pub log_min: f32,
-    pub log_max: f32,
-    pub spacing_log: bool,
+    pub scale: FrequencyScale,
 }
 
 impl BinLayout {
-    pub fn build_layout(num_bins: usize, min_freq: f32, max_freq: f32, log: bool) -> BinLayout {
+    /// Fractional-octave and note scales ignore `num_bins`.
+    pub fn build_layout(
+        num_bins: usize,
+        min_freq: f32,
+        max_freq: f32,
+        scale: FrequencyScale,
+    ) -> BinLayout {
         let min_freq = min_freq.max(1e-6);
         let max_freq = max_freq.max(min_freq + 1.0);
-        let log_min = min_freq.ln();
-        let log_max = max_freq.ln();
-        let mut centers = Vec::with_capacity(num_bins);
-        for i in 0..num_bins {
-            let t = (i as f32 + 0.5) / num_bins as f32;
-            let f = if log {
-                (log_min + t * (log_max - log_min)).exp()
-            } else {
-                min_freq + t * (max_freq - min_freq)
-            };
-            centers.push(f);
-        }
+        let edges = scale.band_edges(num_bins, min_freq, max_freq);
+        let centers = edges
+            .windows(2)
+            .map(|band| scale.from_scale((scale.to_scale(band[0]) + scale.to_scale(band[1])) / 2.0))
+            .collect();
         BinLayout {
             centers,
+            edges,
             min_freq,
             max_freq,
-            log_min,
-            log_max,
-            spacing_log: log,
+            scale,
         }
     }
+
+    pub fn num_bins(&self) -> usize {
+        self.centers.len()
+    }
+
+    /// MIDI note of `bin` in a `Notes` layout.
+    pub fn note(&self, bin: usize) -> Option<u8> {
+        let notes = self.scale.note_range()?;
+        let note = *notes.start() as usize + bin;
+        (note <= *notes.end() as usize).then_some(note as u8)
+    }
+
+    /// Bin whose band contains `freq`, if any.
+    pub fn bin_of(&self, freq: f32) -> Option<usize> {
+        let upper = self.edges.partition_point(|&edge| edge <= freq);
+        if upper == 0 || freq > *self.edges.last()? {
+            return None;
+        }
+        Some((upper - 1).min(self.num_bins() - 1))
+    }
 }
 
 pub trait SpatialFilter: Send + Sync + UiComponent {
@@ -52,6 +72,9 @@ pub trait SpatialFilter: Send + Sync + UiComponent {
 
     fn process(&self, samples: &mut [f32]);
 
+    /// Current parameters, for saving the filter chain.
+    fn preset(&self) -> SpatialFilterPreset;
+
     fn type_id(&self) -> TypeId
     where
         Self: 'static,
@@ -1,4 +1,4 @@
-use crate::filters::TemporalFilter;
+use crate::filters::{TemporalFilter, TemporalFilterPreset};
 
 /// Attack-release filter.
 /// Uses different smoothing factors for attack and release phases.
@@ -39,4 +39,16 @@ impl TemporalFilter for AttackReleaseFilter {
             *x = y;
         }
     }
+
+    fn state_vec(&mut self) -> Option<&mut Vec<f32>> {
+        Some(&mut self.prev)
+    }
+
+    fn preset(&self) -> TemporalFilterPreset {
+        TemporalFilterPreset::AttackRelease {
+            attack: self.attack_alpha,
+            release: self.release_alpha,
+            enabled: self.enabled,
+        }
+    }
 }
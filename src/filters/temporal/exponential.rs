@@ -1,4 +1,4 @@
-use crate::filters::TemporalFilter;
+use crate::filters::{TemporalFilter, TemporalFilterPreset};
 
 /// Simple exponential smoothing filter.
 /// Applies: alpha * x[n] + (1 - alpha) * y[n]
@@ -28,4 +28,12 @@ impl TemporalFilter for ExponentialFilter {
             *x = y;
         }
     }
+
+    fn state_vec(&mut self) -> Option<&mut Vec<f32>> {
+        Some(&mut self.prev)
+    }
+
+    fn preset(&self) -> TemporalFilterPreset {
+        TemporalFilterPreset::Exponential { alpha: self.alpha }
+    }
 }
@@ -6,11 +6,16 @@ pub use attackrelease::AttackReleaseFilter;
 pub use exponential::ExponentialFilter;
 pub use peakholddecay::PeakHoldDecayFilter;
 
+use crate::filters::TemporalFilterPreset;
 use crate::frontend::egui_frontend::UiComponent;
 use std::any::TypeId;
 
 pub trait TemporalFilter: Send + Sync + UiComponent {
     fn process(&mut self, samples: &mut [f32]);
+
+    /// Current parameters, for saving the filter chain.
+    fn preset(&self) -> TemporalFilterPreset;
+
     fn state_vec(&mut self) -> Option<&mut Vec<f32>> {
         None
     }
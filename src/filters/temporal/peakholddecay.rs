@@ -1,4 +1,4 @@
-use crate::filters::TemporalFilter;
+use crate::filters::{TemporalFilter, TemporalFilterPreset};
 
 /// Peak-hold-decay filter.
 /// Holds the peak value and decays it exponentially over time.
@@ -29,4 +29,12 @@ impl TemporalFilter for PeakHoldDecayFilter {
             *x = y;
         }
     }
+
+    fn state_vec(&mut self) -> Option<&mut Vec<f32>> {
+        Some(&mut self.prev)
+    }
+
+    fn preset(&self) -> TemporalFilterPreset {
+        TemporalFilterPreset::PeakHoldDecay { decay: self.decay }
+    }
 }
@@ -1,17 +1,95 @@
 use egui::Color32;
-use egui_plot::{Bar, BarChart, Plot};
+use egui_plot::{Bar, BarChart, HLine, Line, Plot, PlotPoint, PlotPoints, Text};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use crate::Visualizer;
+use crate::audio::device::{HostInfo, list_hosts};
+use crate::preset::{Color, PresetWatcher, is_preset_file};
+use crate::scale::{is_black_key, note_name};
+use crate::{
+    AnalysisMethod, AudioEngine, BinAggregation, BinInterpolation, ChannelSource, Colormap,
+    DeviceSelection, FrequencyScale, GeneratorSettings, NormalizerPreset, OutputMode, Preset,
+    PresetError, ScopeTrace, SpectrogramCursor, SpectrogramUpdate, Transport, Trigger, Visualizer,
+    WindowFunction,
+};
 
 use crate::frontend::egui_frontend::UiComponent;
 
+/// How left and right spectra are drawn in stereo mode.
+#[derive(Clone, Copy, PartialEq)]
+pub enum StereoView {
+    /// Left channel bars go up, right channel bars go down.
+    Mirrored,
+    /// Left channel on the left half, right channel on the right half,
+    /// with the lowest bins meeting in the middle.
+    Split,
+}
+
+/// Where the spectrogram is drawn.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SpectrogramView {
+    Off,
+    /// Instead of the bars.
+    Replace,
+    /// Under the bars.
+    Below,
+}
+
+impl SpectrogramView {
+    pub const ALL: [SpectrogramView; 3] = [
+        SpectrogramView::Off,
+        SpectrogramView::Replace,
+        SpectrogramView::Below,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SpectrogramView::Off => "Off",
+            SpectrogramView::Replace => "Replace bars",
+            SpectrogramView::Below => "Below bars",
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub struct ControlSettings {
     pub num_bins: usize,
     pub window_size: usize,
+    pub zero_padding: usize,
+    pub window_function: WindowFunction,
+    pub overlap: f32,
+    pub channels: usize,
+    pub channel_source: ChannelSource,
+    pub stereo: bool,
+    pub stereo_view: StereoView,
+    pub scale: FrequencyScale,
+    /// Draw a keyboard under the bars of a note layout.
+    pub piano_keys: bool,
+    pub analysis: AnalysisMethod,
+    pub output: OutputMode,
+    /// Kind of normalizer; its parameters are edited on the normalizer itself.
+    pub normalizer: NormalizerPreset,
+    pub interpolation: BinInterpolation,
+    pub aggregation: BinAggregation,
+    pub spectrogram_view: SpectrogramView,
+    /// Frames kept for the spectrogram.
+    pub spectrogram_depth: usize,
+    /// Time covered by the spectrogram in seconds.
+    pub spectrogram_span: f32,
+    pub colormap: Colormap,
+    /// Show the oscilloscope panel.
+    pub scope: bool,
+    pub trigger: Trigger,
+    /// Length of the oscilloscope trace in milliseconds.
+    pub time_base_ms: f32,
+    /// Vertical gain of the oscilloscope.
+    pub scope_gain: f32,
+    /// Draw the right channel too in stereo mode.
+    pub scope_both_channels: bool,
     pub color: egui::Color32,
+    pub right_color: egui::Color32,
 }
 
 impl ControlSettings {
@@ -19,51 +97,438 @@ impl ControlSettings {
         Self {
             num_bins: 50,
             window_size: 2048,
+            zero_padding: 1,
+            window_function: WindowFunction::default(),
+            overlap: 0.75,
+            channels: 1,
+            channel_source: ChannelSource::default(),
+            stereo: false,
+            stereo_view: StereoView::Mirrored,
+            scale: FrequencyScale::default(),
+            piano_keys: true,
+            analysis: AnalysisMethod::default(),
+            output: OutputMode::default(),
+            normalizer: NormalizerPreset::default(),
+            interpolation: BinInterpolation::default(),
+            aggregation: BinAggregation::default(),
+            spectrogram_view: SpectrogramView::Off,
+            spectrogram_depth: 256,
+            spectrogram_span: 0.0,
+            colormap: Colormap::default(),
+            scope: false,
+            trigger: Trigger::default(),
+            time_base_ms: 20.0,
+            scope_gain: 1.0,
+            scope_both_channels: true,
             color: Color32::DARK_BLUE,
+            right_color: Color32::DARK_RED,
         }
     }
 
     fn update_from_visualizer(&mut self, vis: &Visualizer) {
-        self.num_bins = vis.config.num_bins;
-        self.window_size = vis.config.window_size;
+        self.num_bins = vis.config.num_bins();
+        self.window_size = vis.config.window_size();
+        self.zero_padding = vis.config.zero_padding();
+        self.window_function = vis.config.window_function();
+        self.overlap = vis.config.overlap();
+        self.channels = vis.config.channels;
+        self.channel_source = vis.config.channel_source;
+        self.stereo = vis.config.stereo;
+        self.scale = vis.config.scale();
+        self.analysis = vis.config.analysis();
+        self.output = vis.config.output;
+        self.normalizer = vis.config.normalizer_preset();
+        self.interpolation = vis.config.interpolation;
+        self.aggregation = vis.config.aggregation;
+        self.spectrogram_depth = vis.config.spectrogram_depth();
+        self.spectrogram_span = vis.config.spectrogram_span();
+    }
+}
+
+impl From<Color> for Color32 {
+    fn from(color: Color) -> Self {
+        let [r, g, b, a] = color.0;
+        Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
+
+impl From<Color32> for Color {
+    fn from(color: Color32) -> Self {
+        Color(color.to_srgba_unmultiplied())
+    }
+}
+
+/// Playback controls for a shared transport.
+/// While the position slider is dragged, the scrub position is shown instead
+/// of the play position and the seek is issued on release.
+pub struct TransportBar {
+    pub transport: Arc<Transport>,
+    pub scrub: Option<f64>,
+    pub loop_start: f64,
+    pub loop_end: f64,
+}
+
+impl TransportBar {
+    pub fn new(transport: Arc<Transport>) -> Self {
+        let loop_end = transport.duration_seconds().unwrap_or(0.0);
+        Self {
+            transport,
+            scrub: None,
+            loop_start: 0.0,
+            loop_end,
+        }
+    }
+}
+
+/// Host and device picker. Devices are listed when the panel is first shown
+/// and on refresh, since probing them can be slow.
+pub struct DevicePanel {
+    pub hosts: Option<Vec<HostInfo>>,
+    pub selection: DeviceSelection,
+    /// Pick input devices instead of output devices.
+    pub input: bool,
+    /// Name of the device in use.
+    pub active_device: Option<String>,
+}
+
+impl DevicePanel {
+    pub fn new(selection: DeviceSelection, input: bool) -> Self {
+        Self {
+            hosts: None,
+            selection,
+            input,
+            active_device: None,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        self.hosts = Some(list_hosts());
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PresetAction {
+    Load,
+    Save,
+}
+
+/// Preset file picker. The requested action is carried out by the frontend,
+/// which owns the visualizer and the colors.
+pub struct PresetMenu {
+    pub path: String,
+    /// Preset files in the directory of `path`, listed when first needed.
+    pub files: Option<Vec<PathBuf>>,
+    pub action: Option<PresetAction>,
+    /// Reload the active preset when its file changes.
+    pub watch: bool,
+    /// Outcome of the last load or save.
+    pub status: Option<Result<String, String>>,
+}
+
+impl PresetMenu {
+    pub fn new(path: Option<&Path>) -> Self {
+        Self {
+            path: path.map(|p| p.display().to_string()).unwrap_or_default(),
+            files: None,
+            action: None,
+            watch: true,
+            status: None,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        let dir = Path::new(self.path.trim())
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok().map(|e| e.path()))
+                    .filter(|path| path.is_file() && is_preset_file(path))
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        self.files = Some(files);
     }
 }
 
 /// Egui application struct for visualizing a spectrum.
 pub struct EguiFrontend {
+    pub engine: AudioEngine,
     pub visualizer: Arc<Mutex<Visualizer>>,
+    pub transport: Arc<Transport>,
+    /// Settings of the running test signal generator, if that is the source.
+    pub generator: Option<Arc<Mutex<GeneratorSettings>>>,
+    device_panel: DevicePanel,
     last_bins: Vec<f32>,
+    last_right_bins: Option<Vec<f32>>,
+    /// MIDI notes of the bins in a note layout.
+    last_notes: Option<RangeInclusive<u8>>,
+    /// Ring of spectrogram frames, one texture row each.
+    spectrogram_texture: Option<egui::TextureHandle>,
+    /// Texture row of the newest frame; older frames follow below it.
+    spectrogram_top: usize,
+    /// Where the texture continues reading frames.
+    spectrogram_cursor: Option<SpectrogramCursor>,
+    /// Colormap the texture was drawn with.
+    spectrogram_colormap: Colormap,
+    last_scope: Option<ScopeTrace>,
     control_settings: ControlSettings,
+    transport_bar: TransportBar,
+    preset_menu: PresetMenu,
+    /// Watches the preset that was loaded or saved last.
+    preset_watcher: Option<PresetWatcher>,
+    /// Error from reloading the watched preset.
+    preset_error: Option<String>,
+    /// Error from applying the edited control settings.
+    settings_error: Option<String>,
 }
 
 impl EguiFrontend {
-    pub fn new(visualizer: Arc<Mutex<Visualizer>>) -> Self {
+    /// Create the frontend; `preset` is the preset the visualizer was
+    /// configured from, whose colors are used for the bars.
+    pub fn new(engine: AudioEngine, preset: Option<(PathBuf, Preset)>) -> Self {
+        let transport = engine.transport();
+        let mut control_settings = ControlSettings::default();
+        if let Some((_, preset)) = &preset {
+            control_settings.color = preset.colors.bars.into();
+            control_settings.right_color = preset.colors.right.into();
+            control_settings.colormap = preset.colors.colormap;
+        }
         Self {
-            visualizer,
+            visualizer: engine.visualizer(),
+            transport_bar: TransportBar::new(transport.clone()),
+            transport,
+            generator: engine.generator(),
+            device_panel: DevicePanel::new(engine.selection().clone(), engine.uses_input()),
+            engine,
             last_bins: Vec::new(),
-            control_settings: ControlSettings::default(),
+            last_right_bins: None,
+            last_notes: None,
+            spectrogram_texture: None,
+            spectrogram_top: 0,
+            spectrogram_cursor: None,
+            spectrogram_colormap: control_settings.colormap,
+            last_scope: None,
+            control_settings,
+            preset_menu: PresetMenu::new(preset.as_ref().map(|(path, _)| path.as_path())),
+            preset_watcher: preset.map(|(path, _)| PresetWatcher::new(path)),
+            preset_error: None,
+            settings_error: None,
+        }
+    }
+
+    /// Bars of the last spectrum, mono or stereo.
+    fn plot_last(&self, ui: &mut egui::Ui) {
+        match self.last_right_bins.clone() {
+            Some(right) => self.plot_stereo_spectrum(ui, self.last_bins.clone(), right),
+            None => self.plot_spectrum(ui, self.last_bins.clone()),
+        }
+    }
+
+    /// Write the frames of `update` into the spectrogram texture. New frames
+    /// replace the oldest rows of the ring; a reset redraws the whole texture.
+    fn update_spectrogram(&mut self, ctx: &egui::Context, update: SpectrogramUpdate) {
+        let colormap = self.control_settings.colormap;
+        let width = self.last_bins.len().max(1);
+        let height = update.depth.max(1);
+        let options = egui::TextureOptions::LINEAR_REPEAT;
+        let frames = update.frames.iter().filter(|frame| frame.len() == width);
+        self.spectrogram_cursor = Some(update.cursor);
+
+        let texture = match &mut self.spectrogram_texture {
+            Some(texture) if !update.reset && texture.size() == [width, height] => texture,
+            _ => {
+                // Newest frame in the top row
+                let mut pixels = colormap.color(0.0).repeat(width * height);
+                for (row, frame) in frames.rev().take(height).enumerate() {
+                    for (bin, &level) in frame.iter().enumerate() {
+                        let at = (row * width + bin) * 3;
+                        pixels[at..at + 3].copy_from_slice(&colormap.color(level));
+                    }
+                }
+                let image = egui::ColorImage::from_rgb([width, height], &pixels);
+                self.spectrogram_top = 0;
+                match &mut self.spectrogram_texture {
+                    Some(texture) => texture.set(image, options),
+                    None => {
+                        self.spectrogram_texture =
+                            Some(ctx.load_texture("spectrogram", image, options))
+                    }
+                }
+                return;
+            }
+        };
+        for frame in frames {
+            self.spectrogram_top = (self.spectrogram_top + height - 1) % height;
+            let pixels: Vec<u8> = frame.iter().flat_map(|&l| colormap.color(l)).collect();
+            let image = egui::ColorImage::from_rgb([width, 1], &pixels);
+            texture.set_partial([0, self.spectrogram_top], image, options);
         }
     }
 
+    /// Draw the spectrogram into the remaining space, newest frame at the top
+    /// and the lowest bin on the left.
+    fn show_spectrogram(&self, ui: &mut egui::Ui) {
+        let Some(texture) = &self.spectrogram_texture else {
+            return;
+        };
+        let [width, height] = texture.size().map(|size| size.max(1) as f32);
+        let top = self.spectrogram_top as f32;
+        // The ring starts at the newest row and wraps around; the half texel
+        // inset keeps the first and last rows from blending across the seam
+        let uv = egui::Rect::from_min_max(
+            egui::pos2(0.5 / width, (top + 0.5) / height),
+            egui::pos2(1.0 - 0.5 / width, (top + height - 0.5) / height),
+        );
+        let (rect, _) = ui.allocate_exact_size(ui.available_size(), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.image(texture.id(), rect, uv, Color32::WHITE);
+        painter.text(
+            rect.left_top() + egui::vec2(4.0, 4.0),
+            egui::Align2::LEFT_TOP,
+            format!("{:.1} s", self.control_settings.spectrogram_span),
+            egui::FontId::proportional(12.0),
+            Color32::WHITE,
+        );
+    }
+
+    /// Oscilloscope of the last trace, with time in milliseconds from the
+    /// trigger.
+    fn plot_scope(&self, ui: &mut egui::Ui) {
+        let settings = &self.control_settings;
+        let gain = settings.scope_gain as f64;
+        let line = |name: &str, samples: &[f32], rate: u32, color: Color32| {
+            let ms_per_sample = 1000.0 / rate.max(1) as f64;
+            let points: Vec<[f64; 2]> = samples
+                .iter()
+                .enumerate()
+                .map(|(i, &y)| [i as f64 * ms_per_sample, y as f64 * gain])
+                .collect();
+            Line::new(name.to_string(), PlotPoints::from(points)).color(color)
+        };
+
+        Plot::new("scope_plot")
+            .include_x(0.0)
+            .include_x(settings.time_base_ms)
+            .include_y(-1.0)
+            .include_y(1.0)
+            .allow_drag(false)
+            .allow_zoom(false)
+            .allow_scroll(false)
+            .x_axis_label("ms")
+            .show(ui, |plot_ui| {
+                let Some(trace) = &self.last_scope else {
+                    return;
+                };
+                if let Trigger::Rising { level } | Trigger::Falling { level } = settings.trigger {
+                    plot_ui.hline(
+                        HLine::new("Trigger", level as f64 * gain)
+                            .color(Color32::from_gray(90))
+                            .width(0.5),
+                    );
+                }
+                plot_ui.line(line("Left", &trace.left, trace.sample_rate, settings.color));
+                if let Some(right) = &trace.right
+                    && settings.scope_both_channels
+                {
+                    plot_ui.line(line(
+                        "Right",
+                        right,
+                        trace.sample_rate,
+                        settings.right_color,
+                    ));
+                }
+                let status = if trace.triggered {
+                    "Triggered"
+                } else {
+                    "No trigger"
+                };
+                plot_ui.text(
+                    Text::new("Status", PlotPoint::new(0.0, 1.0), status)
+                        .anchor(egui::Align2::LEFT_TOP),
+                );
+            });
+    }
+
     fn plot_spectrum(&self, ui: &mut egui::Ui, bins: Vec<f32>) {
-        let bars: Vec<Bar> = bins
-            .iter()
+        let bars = Self::bars(&bins, self.control_settings.color, |i, y| (i, y));
+        let keys = self.keys(bins.len(), |i| i);
+        self.show_bars(ui, bars, 0.0, keys);
+    }
+
+    fn plot_stereo_spectrum(&self, ui: &mut egui::Ui, left: Vec<f32>, right: Vec<f32>) {
+        let settings = &self.control_settings;
+        let (mut bars, right_bars, min_y, mut keys) = match settings.stereo_view {
+            StereoView::Mirrored => (
+                Self::bars(&left, settings.color, |i, y| (i, y)),
+                Self::bars(&right, settings.right_color, |i, y| (i, -y)),
+                -1.0,
+                Vec::new(),
+            ),
+            StereoView::Split => (
+                Self::bars(&left, settings.color, |i, y| (-i - 1.0, y)),
+                Self::bars(&right, settings.right_color, |i, y| (i, y)),
+                0.0,
+                self.keys(left.len(), |i| -i - 1.0),
+            ),
+        };
+        bars.extend(right_bars);
+        keys.extend(self.keys(right.len(), |i| i));
+        self.show_bars(ui, bars, min_y, keys);
+    }
+
+    /// Build bars from `bins`; `place` maps (bin index, value) to (x, y).
+    fn bars(bins: &[f32], color: Color32, place: impl Fn(f64, f64) -> (f64, f64)) -> Vec<Bar> {
+        bins.iter()
             .enumerate()
-            .filter_map(|(i, &y)| {
-                if y >= 0.0 {
-                    Some(
-                        Bar::new(i as f64, y as f64)
-                            .fill(self.control_settings.color)
-                            .width(1.0_f64),
-                    )
-                } else {
-                    None
-                }
+            .filter(|&(_, &y)| y >= 0.0)
+            .map(|(i, &y)| {
+                let (x, y) = place(i as f64, y as f64);
+                Bar::new(x, y).fill(color).width(1.0_f64)
             })
-            .collect();
+            .collect()
+    }
+
+    /// (x, MIDI note) of each of `len` bins in a note layout; `place` maps the
+    /// bin index to x.
+    fn keys(&self, len: usize, place: impl Fn(f64) -> f64) -> Vec<(f64, u8)> {
+        let Some(notes) = self.last_notes.clone() else {
+            return Vec::new();
+        };
+        notes
+            .take(len)
+            .enumerate()
+            .map(|(i, note)| (place(i as f64), note))
+            .collect()
+    }
+
+    /// Show `bars` with note names under the keys of a note layout, on a
+    /// piano keyboard if enabled, and a level grid in dBFS mode.
+    fn show_bars(&self, ui: &mut egui::Ui, mut bars: Vec<Bar>, min_y: f64, keys: Vec<(f64, u8)>) {
+        let piano_keys = self.control_settings.piano_keys && !keys.is_empty();
+        let key_height = 0.1;
+        let label_y = if piano_keys {
+            min_y - key_height - 0.04
+        } else {
+            min_y - 0.02
+        };
+        for bar in bars.iter_mut() {
+            if let Some(&(_, note)) = keys.iter().find(|(x, _)| *x == bar.argument) {
+                *bar = bar.clone().name(note_name(note));
+            }
+        }
+        let left_x = bars.iter().map(|bar| bar.argument).fold(0.0, f64::min) - 0.5;
+        let output = self.control_settings.output;
 
         Plot::new("fft_plot")
-            .include_y(0.0)
+            .include_y(if keys.is_empty() {
+                min_y
+            } else {
+                label_y - 0.06
+            })
             .include_y(1.0)
             .show_axes(false)
             .show_grid(false)
@@ -71,21 +536,258 @@ impl EguiFrontend {
             .show_x(false)
             .show_y(false)
             .show(ui, |plot_ui| {
+                if let OutputMode::Decibels {
+                    floor_db,
+                    ceiling_db,
+                } = output
+                {
+                    Self::level_grid(plot_ui, floor_db, ceiling_db, left_x, min_y < 0.0);
+                }
                 plot_ui.bar_chart(BarChart::new("Visualizer".to_string(), bars));
+                if piano_keys {
+                    let piano = keys
+                        .iter()
+                        .map(|&(x, note)| {
+                            let fill = if is_black_key(note) {
+                                Color32::BLACK
+                            } else {
+                                Color32::WHITE
+                            };
+                            Bar::new(x, key_height)
+                                .base_offset(min_y - 0.02 - key_height)
+                                .fill(fill)
+                                .stroke(egui::Stroke::new(1.0, Color32::GRAY))
+                                .width(1.0)
+                                .name(note_name(note))
+                        })
+                        .collect();
+                    plot_ui.bar_chart(BarChart::new("Piano".to_string(), piano));
+                }
+                // Name the C of every octave, or every note when there is room
+                let every_note = keys.len() <= 24;
+                for &(x, note) in &keys {
+                    if every_note || note % 12 == 0 {
+                        plot_ui.text(
+                            Text::new("Notes", PlotPoint::new(x, label_y), note_name(note))
+                                .anchor(egui::Align2::CENTER_TOP),
+                        );
+                    }
+                }
             });
     }
+
+    /// Lines every 10 dB between `floor_db` and `ceiling_db`, labelled at
+    /// `left_x`; `mirrored` repeats them below zero.
+    fn level_grid(
+        plot_ui: &mut egui_plot::PlotUi,
+        floor_db: f32,
+        ceiling_db: f32,
+        left_x: f64,
+        mirrored: bool,
+    ) {
+        let color = Color32::from_gray(90);
+        let signs: &[f64] = if mirrored { &[1.0, -1.0] } else { &[1.0] };
+        let mut db = (floor_db / 10.0).ceil() * 10.0;
+        while db <= ceiling_db {
+            let y = ((db - floor_db) / (ceiling_db - floor_db)) as f64;
+            for sign in signs {
+                plot_ui.hline(HLine::new("Level", sign * y).color(color).width(0.5));
+                plot_ui.text(
+                    Text::new(
+                        "Level",
+                        PlotPoint::new(left_x, sign * y),
+                        format!("{} dB", db),
+                    )
+                    .color(color)
+                    .anchor(egui::Align2::LEFT_BOTTOM),
+                );
+            }
+            db += 10.0;
+        }
+    }
+}
+
+impl EguiFrontend {
+    /// Device picker; restarts the engine when the selection changes.
+    fn device_ui(&mut self, ui: &mut egui::Ui) {
+        self.device_panel.active_device = self.engine.device_name().map(str::to_string);
+        self.device_panel.ui(ui);
+
+        if self.device_panel.selection != *self.engine.selection() {
+            let selection = self.device_panel.selection.clone();
+            if let Err(err) = self.engine.set_selection(selection) {
+                self.transport.stop();
+                self.transport.report_error(err);
+            }
+            // The loop end may refer to a different track length now
+            self.transport_bar = TransportBar::new(self.transport.clone());
+        }
+    }
+}
+
+impl EguiFrontend {
+    /// Preset menu; loads or saves when requested.
+    fn preset_ui(&mut self, ui: &mut egui::Ui) {
+        self.preset_menu.ui(ui);
+
+        let Some(action) = self.preset_menu.action.take() else {
+            return;
+        };
+        let path = PathBuf::from(self.preset_menu.path.trim());
+        let result = match action {
+            PresetAction::Load => Preset::load(&path)
+                .and_then(|preset| self.apply_preset(&preset))
+                .map(|()| format!("Loaded {}", path.display())),
+            PresetAction::Save => self.current_preset().save(&path).map(|()| {
+                self.preset_menu.refresh();
+                format!("Saved {}", path.display())
+            }),
+        };
+        // Watch the file even if loading failed, so fixing it takes effect
+        self.preset_watcher = Some(PresetWatcher::new(path));
+        self.preset_error = None;
+        self.preset_menu.status = Some(result.map_err(|err| err.to_string()));
+    }
+
+    /// Reload the watched preset if its file changed. A preset that fails to
+    /// parse leaves the running configuration untouched.
+    fn poll_preset(&mut self) {
+        if !self.preset_menu.watch {
+            return;
+        }
+        let Some(watcher) = &mut self.preset_watcher else {
+            return;
+        };
+        let Some(result) = watcher.poll() else {
+            return;
+        };
+        let name = watcher.path().display().to_string();
+        match result.and_then(|preset| self.apply_preset(&preset)) {
+            Ok(()) => {
+                self.preset_error = None;
+                self.preset_menu.status = Some(Ok(format!("Reloaded {}", name)));
+            }
+            Err(err) => {
+                let message = format!("{}: {}", name, err);
+                self.preset_error = Some(message.clone());
+                self.preset_menu.status = Some(Err(message));
+            }
+        }
+    }
+
+    fn apply_preset(&mut self, preset: &Preset) -> Result<(), PresetError> {
+        if let Ok(mut vis) = self.visualizer.lock() {
+            preset.apply(&mut vis.config)?;
+            self.control_settings.update_from_visualizer(&vis);
+        }
+        self.control_settings.color = preset.colors.bars.into();
+        self.control_settings.right_color = preset.colors.right.into();
+        self.control_settings.colormap = preset.colors.colormap;
+        Ok(())
+    }
+
+    fn current_preset(&self) -> Preset {
+        let mut preset = match self.visualizer.lock() {
+            Ok(vis) => Preset::capture(&vis.config),
+            Err(_) => Preset::default(),
+        };
+        preset.colors.bars = self.control_settings.color.into();
+        preset.colors.right = self.control_settings.right_color.into();
+        preset.colors.colormap = self.control_settings.colormap;
+        preset
+    }
 }
 
 impl eframe::App for EguiFrontend {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_preset();
+
+        if let Some(error) = self.preset_error.clone() {
+            egui::TopBottomPanel::top("preset_error").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        Color32::LIGHT_RED,
+                        format!("⚠ Preset not reloaded: {}", error),
+                    );
+                    if ui.button("Dismiss").clicked() {
+                        self.preset_error = None;
+                    }
+                });
+            });
+        }
+
+        if let Some(error) = self.settings_error.clone() {
+            egui::TopBottomPanel::top("settings_error").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::LIGHT_RED, format!("⚠ Invalid settings: {}", error));
+                    if ui.button("Dismiss").clicked() {
+                        self.settings_error = None;
+                    }
+                });
+            });
+        }
+
+        if let Some(error) = self.transport.error() {
+            egui::TopBottomPanel::top("error").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.colored_label(Color32::LIGHT_RED, format!("⚠ {}", error));
+                    if ui.button("Dismiss").clicked() {
+                        self.transport.clear_error();
+                    }
+                });
+            });
+        }
+
+        egui::TopBottomPanel::bottom("transport").show(ctx, |ui| {
+            self.transport_bar.ui(ui);
+        });
+
+        if self.control_settings.scope {
+            egui::SidePanel::left("scope")
+                .resizable(true)
+                .default_width(400.0)
+                .show(ctx, |ui| {
+                    let settings = self.control_settings;
+                    if let Ok(vis) = self.visualizer.lock() {
+                        let seconds = settings.time_base_ms / 1000.0;
+                        self.last_scope = Some(vis.scope_trace(settings.trigger, seconds));
+                    }
+                    self.plot_scope(ui);
+                });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
+            let view = self.control_settings.spectrogram_view;
+            if self.spectrogram_colormap != self.control_settings.colormap {
+                self.spectrogram_colormap = self.control_settings.colormap;
+                self.spectrogram_cursor = None;
+            }
+            let mut spectrogram = None;
             if let Ok(vis) = self.visualizer.lock() {
-                let bins = vis.visualization_data();
-                self.last_bins = bins.clone();
-                self.plot_spectrum(ui, bins);
-            } else {
-                // on lock error, show last known data
-                self.plot_spectrum(ui, self.last_bins.clone());
+                self.last_notes = vis.config.layout.scale.note_range();
+                if view != SpectrogramView::Off {
+                    spectrogram = Some(vis.spectrogram_since(self.spectrogram_cursor));
+                }
+                if let Some((left, right)) = vis.stereo_visualization_data() {
+                    self.last_bins = left;
+                    self.last_right_bins = Some(right);
+                } else {
+                    self.last_bins = vis.visualization_data();
+                    self.last_right_bins = None;
+                }
+            }
+            if let Some(update) = spectrogram {
+                self.update_spectrogram(ctx, update);
+            }
+            // on lock error, show last known data
+            match view {
+                SpectrogramView::Off => self.plot_last(ui),
+                SpectrogramView::Replace => self.show_spectrogram(ui),
+                SpectrogramView::Below => {
+                    let size = egui::vec2(ui.available_width(), ui.available_height() / 2.0);
+                    ui.allocate_ui(size, |ui| self.plot_last(ui));
+                    self.show_spectrogram(ui);
+                }
             }
         });
 
@@ -101,34 +803,84 @@ impl eframe::App for EguiFrontend {
                         return;
                     };
 
-                    let mut edited_settings = self.control_settings.clone();
+                    let mut edited_settings = self.control_settings;
 
                     edited_settings.ui(ui);
                     ui.separator();
 
                     if let Ok(mut vis) = self.visualizer.lock() {
                         vis.config.filter_manager.ui(ui);
+                        if vis.config.output == OutputMode::Relative {
+                            vis.config.normalizer_mut().ui(ui);
+                        }
                     } else {
                         return;
                     };
 
                     ui.separator();
 
+                    if let Some(generator) = &self.generator
+                        && let Ok(mut settings) = generator.lock()
+                    {
+                        settings.ui(ui);
+                        ui.separator();
+                    }
+
+                    self.device_ui(ui);
+                    ui.separator();
+
                     let changed = edited_settings != self.control_settings;
 
                     if changed {
+                        let previous = self.control_settings;
                         self.control_settings = edited_settings;
                         if let Ok(mut vis) = self.visualizer.lock() {
-                            if vis.config.num_bins != edited_settings.num_bins {
-                                vis.config.set_num_bins(edited_settings.num_bins);
-                            }
-                            if vis.config.window_size != edited_settings.window_size {
-                                vis.config.set_window_size(edited_settings.window_size);
+                            let config = &mut vis.config;
+                            // Each setter on its own, so one invalid value does
+                            // not hold back the others
+                            let results = [
+                                config.set_num_bins(edited_settings.num_bins),
+                                config.set_window(
+                                    edited_settings.window_size,
+                                    edited_settings.zero_padding,
+                                ),
+                                config.set_overlap(edited_settings.overlap),
+                                config.set_analysis(edited_settings.analysis),
+                                if edited_settings.spectrogram_span != previous.spectrogram_span {
+                                    config.set_spectrogram_span(edited_settings.spectrogram_span)
+                                } else {
+                                    config.set_spectrogram_depth(edited_settings.spectrogram_depth)
+                                },
+                            ];
+                            let errors: Vec<String> = results
+                                .into_iter()
+                                .filter_map(Result::err)
+                                .map(|err| err.to_string())
+                                .collect();
+                            self.settings_error = (!errors.is_empty()).then(|| errors.join("; "));
+                            vis.config
+                                .set_window_function(edited_settings.window_function);
+                            vis.config.channel_source = edited_settings.channel_source;
+                            vis.config.stereo = edited_settings.stereo;
+                            vis.config.set_scale(edited_settings.scale);
+                            vis.config.output = edited_settings.output;
+                            if !edited_settings
+                                .normalizer
+                                .same_kind(&vis.config.normalizer_preset())
+                            {
+                                vis.config
+                                    .set_normalizer(edited_settings.normalizer.build());
                             }
+                            vis.config.interpolation = edited_settings.interpolation;
+                            vis.config.aggregation = edited_settings.aggregation;
                             let layout = vis.config.layout.clone();
                             vis.config.filter_manager.update_layout(layout);
                         }
                     }
+
+                    // After applying the edited settings, so a loaded preset is not
+                    // overwritten by them
+                    self.preset_ui(ui);
                 });
             });
 
@@ -138,12 +890,11 @@ impl eframe::App for EguiFrontend {
 }
 
 impl crate::frontend::VisualizerFrontend for EguiFrontend {
-    fn run(&self) {
-        let visualizer = Arc::clone(&self.visualizer);
+    fn run(self: Box<Self>) {
         eframe::run_native(
             "Visualizer (egui)",
             eframe::NativeOptions::default(),
-            Box::new(move |_| Ok(Box::new(EguiFrontend::new(visualizer.clone())))),
+            Box::new(move |_| Ok(self)),
         )
         .ok();
     }
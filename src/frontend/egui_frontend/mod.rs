@@ -1,5 +1,8 @@
 pub mod egui_vis;
 pub mod ui_components;
 
-pub use egui_vis::{ControlSettings, EguiFrontend};
+pub use egui_vis::{
+    ControlSettings, DevicePanel, EguiFrontend, PresetAction, PresetMenu, SpectrogramView,
+    StereoView, TransportBar,
+};
 pub use ui_components::UiComponent;
@@ -1,6 +1,22 @@
+use crate::analysis::{MAX_BINS_PER_OCTAVE, MAX_Q};
+use crate::audio::device::SAMPLE_FORMATS;
 use crate::filters::registry::spatial_factories;
 use crate::filters::*;
-use crate::frontend::egui_frontend::ControlSettings;
+use crate::frontend::egui_frontend::{
+    ControlSettings, DevicePanel, PresetAction, PresetMenu, SpectrogramView, StereoView,
+    TransportBar,
+};
+use crate::normalizers::{
+    AgcNormalizer, FixedGainNormalizer, PeakNormalizer, PercentileNormalizer, RmsLoudnessNormalizer,
+};
+use crate::scale::note_name;
+use crate::spectrogram::MAX_SPECTROGRAM_DEPTH;
+use crate::transport::EndBehavior;
+use crate::visualizer::{MAX_FFT_SIZE, MAX_OVERLAP, MIN_WINDOW_SIZE};
+use crate::{
+    AnalysisMethod, BinAggregation, BinInterpolation, ChannelSource, Colormap, FrequencyScale,
+    GeneratorSettings, NormalizerPreset, OutputMode, Signal, Trigger, WindowFunction,
+};
 use egui;
 
 pub trait UiComponent {
@@ -17,11 +33,16 @@ fn add_checkbox(ui: &mut egui::Ui, enabled: &mut bool, label: &str) {
             ui.add_enabled_ui(false, |ui| {
                 ui.label(format!("{} (disabled)", label));
             });
-            return;
         }
     });
 }
 
+/// Format seconds as `m:ss.t`.
+fn format_time(seconds: f64) -> String {
+    let tenths = (seconds.max(0.0) * 10.0) as u64;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
 impl UiComponent for FilterManager {
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.label("Spatial Filters:");
@@ -49,10 +70,10 @@ impl UiComponent for FilterManager {
                     }
                 });
 
-                if let Some(entry) = self.spatial_filters().iter().find(|e| e.id == *id) {
-                    if let Some(mut f) = entry.try_lock() {
-                        f.ui(ui);
-                    }
+                if let Some(entry) = self.spatial_filters().iter().find(|e| e.id == *id)
+                    && let Some(mut f) = entry.try_lock()
+                {
+                    f.ui(ui);
                 }
             });
         }
@@ -63,14 +84,8 @@ impl UiComponent for FilterManager {
                     continue;
                 }
                 if ui.button(f.name).clicked() {
-                    if f.name == "Gaussian" {
-                        self.add_spatial_filter(GaussianFilter::default());
-                        ui.close();
-                    }
-                    if f.name == "A-Weighting" {
-                        self.add_spatial_filter(AWeightingFilter::default());
-                        ui.close();
-                    }
+                    self.add_spatial_from_factory(&f);
+                    ui.close();
                 }
             }
             if spatial_factories()
@@ -144,7 +159,7 @@ impl UiComponent for AWeightingFilter {
             if !self.enabled {
                 return;
             }
-            ui.label(format!("{}", label));
+            ui.label(label);
         });
     }
 
@@ -193,27 +208,918 @@ impl UiComponent for ExponentialFilter {
     }
 }
 
+impl UiComponent for RmsLoudnessNormalizer {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", self.group_name()));
+            let (floor, reference) = (self.floor, self.reference);
+            ui.add(
+                egui::DragValue::new(&mut self.floor)
+                    .speed(0.001)
+                    .range(0.0..=reference - 0.001)
+                    .prefix("floor="),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.reference)
+                    .speed(0.01)
+                    .range(floor + 0.001..=2.0)
+                    .prefix("ref="),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.gamma)
+                    .speed(0.01)
+                    .range(0.05..=4.0)
+                    .prefix("γ="),
+            );
+        });
+    }
+
+    fn group_name(&self) -> &'static str {
+        "RMS loudness"
+    }
+}
+
+impl UiComponent for PeakNormalizer {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", self.group_name()));
+            ui.add(
+                egui::DragValue::new(&mut self.target)
+                    .speed(0.01)
+                    .range(0.01..=1.0)
+                    .prefix("target="),
+            );
+        });
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Peak"
+    }
+}
+
+impl UiComponent for AgcNormalizer {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", self.group_name()));
+            ui.add(
+                egui::DragValue::new(&mut self.target)
+                    .speed(0.01)
+                    .range(0.01..=1.0)
+                    .prefix("target="),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.attack)
+                    .speed(0.01)
+                    .range(0.0..=1.0)
+                    .prefix("attack="),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.release)
+                    .speed(0.001)
+                    .range(0.0..=1.0)
+                    .prefix("release="),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Max gain:");
+            ui.add(
+                egui::DragValue::new(&mut self.max_gain_db)
+                    .range(0.0..=120.0)
+                    .suffix(" dB"),
+            );
+        });
+    }
+
+    fn group_name(&self) -> &'static str {
+        "AGC"
+    }
+}
+
+impl UiComponent for FixedGainNormalizer {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", self.group_name()));
+            ui.add(
+                egui::DragValue::new(&mut self.gain_db)
+                    .range(-60.0..=120.0)
+                    .suffix(" dB"),
+            );
+        });
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Gain"
+    }
+}
+
+impl UiComponent for PercentileNormalizer {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", self.group_name()));
+            ui.add(
+                egui::DragValue::new(&mut self.percentile)
+                    .speed(0.005)
+                    .range(0.0..=1.0)
+                    .prefix("p="),
+            );
+            ui.add(
+                egui::DragValue::new(&mut self.frames)
+                    .speed(1)
+                    .range(1..=10_000)
+                    .suffix(" frames"),
+            );
+        });
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Percentile"
+    }
+}
+
 impl UiComponent for ControlSettings {
     fn ui(&mut self, ui: &mut egui::Ui) {
         ui.label("General:");
         ui.horizontal(|ui| {
             ui.label("Bins:");
             ui.style_mut().spacing.slider_width = 200.0;
-            ui.add(egui::Slider::new(&mut self.num_bins, 8..=512));
+            let fixed_by_analysis = matches!(self.analysis, AnalysisMethod::ConstantQ { .. });
+            ui.add_enabled(
+                self.scale.uses_num_bins() && !fixed_by_analysis,
+                egui::Slider::new(&mut self.num_bins, 8..=512),
+            )
+            .on_disabled_hover_text("Set by the scale or the constant-Q bins per octave");
         });
         ui.horizontal(|ui| {
             ui.label("Window:");
             for size in [512, 1024, 2048, 4096, 8192] {
                 ui.selectable_value(&mut self.window_size, size, size.to_string());
             }
+            ui.add(
+                egui::DragValue::new(&mut self.window_size)
+                    .range(MIN_WINDOW_SIZE..=MAX_FFT_SIZE / self.zero_padding),
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.label("Padding:");
+            for factor in [1, 2, 4, 8] {
+                let fits = self.window_size.next_power_of_two() * factor <= MAX_FFT_SIZE;
+                ui.add_enabled_ui(fits, |ui| {
+                    ui.selectable_value(&mut self.zero_padding, factor, format!("{}×", factor));
+                });
+            }
+            let fft_size = self.window_size.next_power_of_two() * self.zero_padding;
+            ui.label(format!("FFT {}", fft_size));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Taper:");
+            egui::ComboBox::from_id_salt("window_function")
+                .selected_text(self.window_function.label())
+                .show_ui(ui, |ui| {
+                    for function in WindowFunction::options() {
+                        let selected = self.window_function.same_kind(&function);
+                        if ui.selectable_label(selected, function.label()).clicked() && !selected {
+                            self.window_function = function;
+                        }
+                    }
+                });
+            if let WindowFunction::Kaiser { beta } = &mut self.window_function {
+                ui.add(
+                    egui::DragValue::new(beta)
+                        .speed(0.1)
+                        .range(0.0..=50.0)
+                        .prefix("β="),
+                );
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Overlap:");
+            let hop = ((self.window_size as f32 * (1.0 - self.overlap)).round() as usize).max(1);
+            ui.add(
+                egui::Slider::new(&mut self.overlap, 0.0..=MAX_OVERLAP)
+                    .custom_formatter(|v, _| format!("{:.0}%", v * 100.0))
+                    .custom_parser(|s| {
+                        s.trim_end_matches('%')
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                            .map(|v| v / 100.0)
+                    }),
+            );
+            ui.label(format!("hop {}", hop));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Scale:");
+            egui::ComboBox::from_id_salt("frequency_scale")
+                .selected_text(self.scale.label())
+                .show_ui(ui, |ui| {
+                    for scale in FrequencyScale::options() {
+                        let selected = self.scale.same_kind(&scale);
+                        if ui.selectable_label(selected, scale.label()).clicked() && !selected {
+                            self.scale = scale;
+                        }
+                    }
+                });
+        });
+        if let FrequencyScale::Notes {
+            a4,
+            lowest,
+            highest,
+        } = &mut self.scale
+        {
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(a4)
+                        .speed(0.1)
+                        .range(400.0..=480.0)
+                        .prefix("A4=")
+                        .suffix(" Hz"),
+                );
+                let note = |value: f64, _| note_name(value as u8);
+                let high = *highest;
+                ui.add(
+                    egui::DragValue::new(lowest)
+                        .speed(0.2)
+                        .range(0..=high)
+                        .custom_formatter(note),
+                );
+                ui.label("to");
+                let low = *lowest;
+                ui.add(
+                    egui::DragValue::new(highest)
+                        .speed(0.2)
+                        .range(low..=127)
+                        .custom_formatter(note),
+                );
+                ui.checkbox(&mut self.piano_keys, "Piano keys");
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("Level:");
+            egui::ComboBox::from_id_salt("output_mode")
+                .selected_text(self.output.label())
+                .show_ui(ui, |ui| {
+                    for mode in OutputMode::options() {
+                        let selected = self.output.same_kind(&mode);
+                        if ui.selectable_label(selected, mode.label()).clicked() && !selected {
+                            self.output = mode;
+                        }
+                    }
+                });
+            if let OutputMode::Decibels {
+                floor_db,
+                ceiling_db,
+            } = &mut self.output
+            {
+                let (floor, ceiling) = (*floor_db, *ceiling_db);
+                ui.add(
+                    egui::DragValue::new(floor_db)
+                        .range(-200.0..=ceiling - 1.0)
+                        .suffix(" dB"),
+                );
+                ui.label("to");
+                ui.add(
+                    egui::DragValue::new(ceiling_db)
+                        .range(floor + 1.0..=20.0)
+                        .suffix(" dB"),
+                );
+            }
         });
+        if self.output == OutputMode::Relative {
+            ui.horizontal(|ui| {
+                ui.label("Normalize:");
+                egui::ComboBox::from_id_salt("normalizer")
+                    .selected_text(self.normalizer.label())
+                    .show_ui(ui, |ui| {
+                        for normalizer in NormalizerPreset::options() {
+                            let selected = self.normalizer.same_kind(&normalizer);
+                            if ui.selectable_label(selected, normalizer.label()).clicked()
+                                && !selected
+                            {
+                                self.normalizer = normalizer;
+                            }
+                        }
+                    });
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("Analysis:");
+            egui::ComboBox::from_id_salt("analysis_method")
+                .selected_text(self.analysis.label())
+                .show_ui(ui, |ui| {
+                    for method in AnalysisMethod::options() {
+                        let selected = self.analysis.same_kind(&method);
+                        if ui.selectable_label(selected, method.label()).clicked() && !selected {
+                            self.analysis = method;
+                        }
+                    }
+                });
+            if let AnalysisMethod::ConstantQ { bins_per_octave, q } = &mut self.analysis {
+                ui.add(
+                    egui::DragValue::new(bins_per_octave)
+                        .range(1..=MAX_BINS_PER_OCTAVE)
+                        .suffix("/oct"),
+                );
+                ui.add(
+                    egui::DragValue::new(q)
+                        .speed(0.1)
+                        .range(1.0..=MAX_Q)
+                        .prefix("Q="),
+                );
+            }
+        });
+        if let AnalysisMethod::MultiResolution {
+            bass_window,
+            mid_window,
+            treble_window,
+            low_crossover,
+            high_crossover,
+        } = &mut self.analysis
+        {
+            ui.horizontal(|ui| {
+                let (bass, mid, treble) = (*bass_window, *mid_window, *treble_window);
+                ui.label("Bass:");
+                ui.add(egui::DragValue::new(bass_window).range(mid..=MAX_FFT_SIZE));
+                ui.label("Mid:");
+                ui.add(egui::DragValue::new(mid_window).range(treble..=bass));
+                ui.label("Treble:");
+                ui.add(egui::DragValue::new(treble_window).range(MIN_WINDOW_SIZE..=mid));
+            })
+            .response
+            .on_hover_text("Window sizes of the bass, mid and treble FFTs");
+            ui.horizontal(|ui| {
+                let (low, high) = (*low_crossover, *high_crossover);
+                ui.label("Crossovers:");
+                ui.add(
+                    egui::DragValue::new(low_crossover)
+                        .speed(1.0)
+                        .range(1.0..=high - 1.0)
+                        .suffix(" Hz"),
+                );
+                ui.add(
+                    egui::DragValue::new(high_crossover)
+                        .speed(10.0)
+                        .range(low + 1.0..=24000.0)
+                        .suffix(" Hz"),
+                );
+            });
+        }
+        let fft = !matches!(self.analysis, AnalysisMethod::ConstantQ { .. });
+        ui.add_enabled_ui(fft, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Fill:");
+                egui::ComboBox::from_id_salt("bin_interpolation")
+                    .selected_text(self.interpolation.label())
+                    .show_ui(ui, |ui| {
+                        for interpolation in BinInterpolation::ALL {
+                            ui.selectable_value(
+                                &mut self.interpolation,
+                                interpolation,
+                                interpolation.label(),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text("Interpolate bins between FFT lines");
+                ui.label("Combine:");
+                egui::ComboBox::from_id_salt("bin_aggregation")
+                    .selected_text(self.aggregation.label())
+                    .show_ui(ui, |ui| {
+                        for aggregation in BinAggregation::ALL {
+                            ui.selectable_value(
+                                &mut self.aggregation,
+                                aggregation,
+                                aggregation.label(),
+                            );
+                        }
+                    })
+                    .response
+                    .on_hover_text("How FFT lines within a bin are combined");
+            })
+        });
+        ui.horizontal(|ui| {
+            ui.label("Channel:");
+            egui::ComboBox::from_id_salt("channel_source")
+                .selected_text(self.channel_source.label())
+                .show_ui(ui, |ui| {
+                    for source in ChannelSource::options(self.channels) {
+                        ui.selectable_value(&mut self.channel_source, source, source.label());
+                    }
+                });
+        });
+        if self.channels >= 2 {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.stereo, "Stereo");
+                ui.add_enabled_ui(self.stereo, |ui| {
+                    ui.selectable_value(&mut self.stereo_view, StereoView::Mirrored, "Mirrored");
+                    ui.selectable_value(&mut self.stereo_view, StereoView::Split, "Split");
+                });
+            });
+        }
         ui.horizontal(|ui| {
             ui.label("Color:");
             ui.color_edit_button_srgba(&mut self.color);
+            if self.stereo {
+                ui.label("Right:");
+                ui.color_edit_button_srgba(&mut self.right_color);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Spectrogram:");
+            egui::ComboBox::from_id_salt("spectrogram_view")
+                .selected_text(self.spectrogram_view.label())
+                .show_ui(ui, |ui| {
+                    for view in SpectrogramView::ALL {
+                        ui.selectable_value(&mut self.spectrogram_view, view, view.label());
+                    }
+                });
+            if self.spectrogram_view != SpectrogramView::Off {
+                egui::ComboBox::from_id_salt("colormap")
+                    .selected_text(self.colormap.label())
+                    .show_ui(ui, |ui| {
+                        for colormap in Colormap::ALL {
+                            ui.selectable_value(&mut self.colormap, colormap, colormap.label());
+                        }
+                    });
+            }
         });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.scope, "Oscilloscope");
+            if self.scope && self.stereo {
+                ui.checkbox(&mut self.scope_both_channels, "Both channels");
+            }
+        });
+        if self.scope {
+            ui.horizontal(|ui| {
+                ui.label("Trigger:");
+                egui::ComboBox::from_id_salt("scope_trigger")
+                    .selected_text(self.trigger.label())
+                    .show_ui(ui, |ui| {
+                        for trigger in Trigger::options() {
+                            let selected = self.trigger.same_kind(&trigger);
+                            if ui.selectable_label(selected, trigger.label()).clicked() && !selected
+                            {
+                                self.trigger = trigger;
+                            }
+                        }
+                    });
+                if let Trigger::Rising { level } | Trigger::Falling { level } = &mut self.trigger {
+                    ui.add(
+                        egui::DragValue::new(level)
+                            .speed(0.01)
+                            .range(-1.0..=1.0)
+                            .prefix("level="),
+                    );
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Time:");
+                ui.add(
+                    egui::DragValue::new(&mut self.time_base_ms)
+                        .speed(0.5)
+                        .range(0.5..=500.0)
+                        .suffix(" ms"),
+                );
+                ui.label("Gain:");
+                ui.add(
+                    egui::DragValue::new(&mut self.scope_gain)
+                        .speed(0.05)
+                        .range(0.1..=100.0)
+                        .prefix("×"),
+                );
+            });
+        }
+        if self.spectrogram_view != SpectrogramView::Off {
+            ui.horizontal(|ui| {
+                ui.label("History:");
+                ui.add(
+                    egui::DragValue::new(&mut self.spectrogram_depth)
+                        .speed(1)
+                        .range(1..=MAX_SPECTROGRAM_DEPTH)
+                        .suffix(" frames"),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut self.spectrogram_span)
+                        .speed(0.05)
+                        .range(0.01..=600.0)
+                        .max_decimals(2)
+                        .suffix(" s"),
+                );
+            });
+        }
     }
 
     fn group_name(&self) -> &'static str {
         "Control Settings"
     }
 }
+
+/// Logarithmic frequency slider for generator parameters.
+fn frequency_slider(ui: &mut egui::Ui, frequency: &mut f32, range: std::ops::RangeInclusive<f32>) {
+    ui.add(
+        egui::Slider::new(frequency, range)
+            .logarithmic(true)
+            .suffix(" Hz"),
+    );
+}
+
+impl UiComponent for GeneratorSettings {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.label("Generator:");
+        ui.horizontal(|ui| {
+            ui.label("Signal:");
+            egui::ComboBox::from_id_salt("generator_signal")
+                .selected_text(self.signal.label())
+                .show_ui(ui, |ui| {
+                    for signal in Signal::options() {
+                        let selected = self.signal.same_kind(&signal);
+                        if ui.selectable_label(selected, signal.label()).clicked() && !selected {
+                            self.signal = signal;
+                        }
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Level:");
+            ui.add(egui::Slider::new(&mut self.level_db, -60.0..=0.0).suffix(" dBFS"));
+        });
+
+        match &mut self.signal {
+            Signal::Sine { frequency }
+            | Signal::Square { frequency }
+            | Signal::Saw { frequency } => {
+                ui.horizontal(|ui| {
+                    ui.label("Frequency:");
+                    frequency_slider(ui, frequency, 10.0..=20000.0);
+                });
+            }
+            Signal::Impulse { frequency } => {
+                ui.horizontal(|ui| {
+                    ui.label("Rate:");
+                    frequency_slider(ui, frequency, 0.1..=1000.0);
+                });
+            }
+            Signal::Sweep {
+                start,
+                end,
+                duration,
+            } => {
+                ui.horizontal(|ui| {
+                    ui.label("From:");
+                    frequency_slider(ui, start, 10.0..=20000.0);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("To:");
+                    frequency_slider(ui, end, 10.0..=20000.0);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Duration:");
+                    ui.add(
+                        egui::DragValue::new(duration)
+                            .speed(0.1)
+                            .range(0.1..=120.0)
+                            .suffix(" s"),
+                    );
+                });
+            }
+            Signal::MultiTone { frequencies } => {
+                let mut remove = None;
+                for (i, frequency) in frequencies.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        frequency_slider(ui, frequency, 10.0..=20000.0);
+                        if ui.small_button("✖").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    frequencies.remove(i);
+                }
+                if ui.button("Add tone").clicked() {
+                    let next = frequencies
+                        .last()
+                        .map_or(1000.0, |f| (f * 2.0).min(20000.0));
+                    frequencies.push(next);
+                }
+            }
+            Signal::WhiteNoise | Signal::PinkNoise | Signal::BrownNoise => {}
+        }
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Generator"
+    }
+}
+
+impl UiComponent for PresetMenu {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Preset:");
+            ui.menu_button("Open", |ui| {
+                if self.files.is_none() {
+                    self.refresh();
+                }
+                let files = self.files.as_deref().unwrap_or_default();
+                if files.is_empty() {
+                    ui.label("No presets found");
+                }
+                let mut chosen = None;
+                for file in files {
+                    let name = file.file_name().unwrap_or_default().to_string_lossy();
+                    if ui.button(name).clicked() {
+                        chosen = Some(file.display().to_string());
+                        ui.close();
+                    }
+                }
+                if let Some(path) = chosen {
+                    self.path = path;
+                    self.action = Some(PresetAction::Load);
+                }
+            });
+            if ui
+                .small_button("⟳")
+                .on_hover_text("Refresh presets")
+                .clicked()
+            {
+                self.refresh();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.path)
+                    .hint_text("preset.toml or .json")
+                    .desired_width(170.0),
+            );
+            let has_path = !self.path.trim().is_empty();
+            if ui
+                .add_enabled(has_path, egui::Button::new("Load"))
+                .clicked()
+            {
+                self.action = Some(PresetAction::Load);
+            }
+            if ui
+                .add_enabled(has_path, egui::Button::new("Save"))
+                .clicked()
+            {
+                self.action = Some(PresetAction::Save);
+            }
+        });
+        ui.checkbox(&mut self.watch, "Reload on change")
+            .on_hover_text("Apply edits to the loaded preset file while running");
+        match &self.status {
+            Some(Ok(message)) => {
+                ui.label(message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(egui::Color32::LIGHT_RED, message);
+            }
+            None => {}
+        }
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Preset"
+    }
+}
+
+/// Frames per callback offered in the device panel.
+const BUFFER_SIZES: [u32; 7] = [64, 128, 256, 512, 1024, 2048, 4096];
+
+impl UiComponent for DevicePanel {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Audio Device:");
+            if ui
+                .small_button("⟳")
+                .on_hover_text("Refresh devices")
+                .clicked()
+            {
+                self.refresh();
+            }
+        });
+        if self.hosts.is_none() {
+            self.refresh();
+        }
+        let hosts = self.hosts.as_deref().unwrap_or_default();
+
+        ui.horizontal(|ui| {
+            ui.label("Host:");
+            let default_host = hosts.iter().find(|h| h.is_default).map(|h| h.id);
+            let host_label = |id: Option<cpal::HostId>| match id {
+                Some(id) => id.name().to_string(),
+                None => format!("Default ({})", default_host.map_or("none", |id| id.name())),
+            };
+            egui::ComboBox::from_id_salt("audio_host")
+                .selected_text(host_label(self.selection.host))
+                .show_ui(ui, |ui| {
+                    let mut host = self.selection.host;
+                    ui.selectable_value(&mut host, None, host_label(None));
+                    for info in hosts {
+                        ui.selectable_value(&mut host, Some(info.id), host_label(Some(info.id)));
+                    }
+                    if host != self.selection.host {
+                        // Device names are host specific
+                        self.selection.host = host;
+                        self.selection.input = None;
+                        self.selection.output = None;
+                    }
+                });
+        });
+
+        let host = match self.selection.host {
+            Some(id) => hosts.iter().find(|h| h.id == id),
+            None => hosts.iter().find(|h| h.is_default),
+        };
+        let devices = match (host, self.input) {
+            (Some(host), true) => host.inputs.as_slice(),
+            (Some(host), false) => host.outputs.as_slice(),
+            (None, _) => &[],
+        };
+        let selected = if self.input {
+            &mut self.selection.input
+        } else {
+            &mut self.selection.output
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(if self.input { "Input:" } else { "Output:" });
+            let default_name = devices
+                .iter()
+                .find(|d| d.is_default)
+                .map(|d| d.name.as_str());
+            let device_label = |name: &Option<String>| match name {
+                Some(name) => name.clone(),
+                None => format!("Default ({})", default_name.unwrap_or("none")),
+            };
+            egui::ComboBox::from_id_salt("audio_device")
+                .selected_text(device_label(selected))
+                .width(200.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(selected, None, device_label(&None));
+                    for device in devices {
+                        let name = Some(device.name.clone());
+                        let label = device_label(&name);
+                        ui.selectable_value(selected, name, label);
+                    }
+                });
+        });
+
+        let device = match selected {
+            Some(name) => devices.iter().find(|d| &d.name == name),
+            None => devices.iter().find(|d| d.is_default),
+        };
+        let options = &mut self.selection.options;
+
+        ui.horizontal(|ui| {
+            ui.label("Buffer:");
+            let buffer_label = |size: Option<u32>| match size {
+                Some(size) => format!("{} frames", size),
+                None => "Default".to_string(),
+            };
+            egui::ComboBox::from_id_salt("audio_buffer")
+                .selected_text(buffer_label(options.buffer_size))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut options.buffer_size, None, buffer_label(None));
+                    for size in BUFFER_SIZES {
+                        ui.selectable_value(
+                            &mut options.buffer_size,
+                            Some(size),
+                            buffer_label(Some(size)),
+                        );
+                    }
+                });
+
+            ui.label("Format:");
+            let mut formats: Vec<cpal::SampleFormat> = match device {
+                Some(device) => device.configs.iter().map(|c| c.sample_format).collect(),
+                None => SAMPLE_FORMATS.to_vec(),
+            };
+            formats.retain(|format| SAMPLE_FORMATS.contains(format));
+            formats.sort_by_key(|format| format.to_string());
+            formats.dedup();
+            let format_label = |format: Option<cpal::SampleFormat>| match format {
+                Some(format) => format.to_string(),
+                None => "Default".to_string(),
+            };
+            egui::ComboBox::from_id_salt("audio_format")
+                .selected_text(format_label(options.sample_format))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut options.sample_format, None, format_label(None));
+                    for format in formats {
+                        ui.selectable_value(
+                            &mut options.sample_format,
+                            Some(format),
+                            format_label(Some(format)),
+                        );
+                    }
+                });
+        });
+
+        if let Some(device) = device {
+            egui::CollapsingHeader::new("Supported configs")
+                .id_salt("audio_configs")
+                .show(ui, |ui| {
+                    if device.configs.is_empty() {
+                        ui.label("None reported");
+                    }
+                    for config in &device.configs {
+                        ui.label(config.to_string());
+                    }
+                });
+        }
+        match &self.active_device {
+            Some(name) => ui.label(format!("Running on {}", name)),
+            None => ui.colored_label(egui::Color32::LIGHT_RED, "Not running"),
+        };
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Audio Device"
+    }
+}
+
+impl UiComponent for TransportBar {
+    fn ui(&mut self, ui: &mut egui::Ui) {
+        let transport = self.transport.clone();
+        let duration = transport.duration_seconds();
+
+        ui.horizontal(|ui| {
+            let play_label = if transport.is_playing() { "⏸" } else { "▶" };
+            if ui.button(play_label).clicked() {
+                transport.toggle_play();
+            }
+            if ui.button("⏹").clicked() {
+                transport.stop();
+                self.scrub = None;
+            }
+
+            let mut position = self.scrub.unwrap_or_else(|| transport.position_seconds());
+            match duration {
+                Some(duration) => {
+                    ui.style_mut().spacing.slider_width = (ui.available_width() - 260.0).max(100.0);
+                    let response =
+                        ui.add(egui::Slider::new(&mut position, 0.0..=duration).show_value(false));
+                    if response.dragged() {
+                        self.scrub = Some(position);
+                    } else if response.drag_stopped() || response.changed() {
+                        transport.seek(position);
+                        self.scrub = None;
+                    }
+                    ui.label(format!(
+                        "{} / {}",
+                        format_time(position),
+                        format_time(duration)
+                    ));
+
+                    let mut loop_at_end = transport.end_behavior() == EndBehavior::Loop;
+                    if ui.checkbox(&mut loop_at_end, "Loop").changed() {
+                        transport.set_end_behavior(if loop_at_end {
+                            EndBehavior::Loop
+                        } else {
+                            EndBehavior::Stop
+                        });
+                    }
+                }
+                None => {
+                    ui.label(format_time(position));
+                }
+            }
+        });
+
+        // Looping only applies to tracks with a known length
+        let Some(max) = duration else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            let mut loop_region = transport.loop_region().is_some();
+            let mut changed = ui.checkbox(&mut loop_region, "Loop region").changed();
+            ui.add_enabled_ui(loop_region, |ui| {
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.loop_start)
+                            .speed(0.1)
+                            .range(0.0..=max)
+                            .custom_formatter(|v, _| format_time(v))
+                            .prefix("from "),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.loop_end)
+                            .speed(0.1)
+                            .range(0.0..=max)
+                            .custom_formatter(|v, _| format_time(v))
+                            .prefix("to "),
+                    )
+                    .changed();
+            });
+            if changed {
+                transport.set_loop_region(loop_region.then_some((self.loop_start, self.loop_end)));
+            }
+        });
+    }
+
+    fn group_name(&self) -> &'static str {
+        "Transport"
+    }
+}
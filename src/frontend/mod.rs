@@ -4,12 +4,12 @@ pub mod egui_frontend;
 pub use egui_frontend::EguiFrontend;
 // pub use bevy_vis::BevyApp;
 
-use crate::Visualizer;
+use std::path::PathBuf;
 
-use std::sync::{Arc, Mutex};
+use crate::{AudioEngine, Preset};
 
 pub trait VisualizerFrontend {
-    fn run(&self);
+    fn run(self: Box<Self>);
 }
 
 pub enum FrontendKind {
@@ -17,12 +17,15 @@ pub enum FrontendKind {
     // Bevy,
 }
 
+/// Create a frontend for `engine`. `preset` is the preset file the
+/// visualizer was configured from, if any.
 pub fn make_frontend(
     kind: FrontendKind,
-    visualizer: Arc<Mutex<Visualizer>>,
+    engine: AudioEngine,
+    preset: Option<(PathBuf, Preset)>,
 ) -> Box<dyn VisualizerFrontend> {
     match kind {
-        FrontendKind::Egui => Box::new(EguiFrontend::new(visualizer)),
+        FrontendKind::Egui => Box::new(EguiFrontend::new(engine, preset)),
         // FrontendKind::Bevy => Box::new(bevy_vis::BevyFrontend::new(visualizer)),
     }
 }
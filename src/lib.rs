@@ -1,9 +1,36 @@
+pub mod analysis;
+pub mod audio;
+pub mod binning;
+pub mod channels;
+pub mod error;
 pub mod filters;
 pub mod frontend;
+pub mod normalizers;
+pub mod preset;
+pub mod scale;
+pub mod scope;
+pub mod spectrogram;
+pub mod transport;
 pub mod visualizer;
+pub mod window;
 
+pub use analysis::{AnalysisMethod, SpectrumBackend};
+pub use audio::{
+    AudioEngine, AudioSource, DeviceSelection, FileSource, GeneratorSettings, GeneratorSource,
+    InputSource, Player, Signal, SourceSpec, StreamOptions,
+};
+pub use binning::{BinAggregation, BinInterpolation};
+pub use channels::ChannelSource;
+pub use error::{AudioError, ConfigError, PresetError};
 pub use frontend::{FrontendKind, make_frontend};
-pub use visualizer::Visualizer;
+pub use normalizers::{Normalizer, NormalizerPreset};
+pub use preset::{Preset, PresetWatcher};
+pub use scale::FrequencyScale;
+pub use scope::{Scope, ScopeTrace, Trigger};
+pub use spectrogram::{Colormap, Spectrogram, SpectrogramCursor, SpectrogramUpdate};
+pub use transport::{EndBehavior, PlaybackState, Transport};
+pub use visualizer::{OutputMode, Visualizer};
+pub use window::WindowFunction;
 // pub use frontend::bevy_vis::BevyApp;
 
 pub use filters::{ExponentialFilter, GaussianFilter, SpatialFilter, TemporalFilter};
@@ -1,76 +1,138 @@
 use std::sync::{Arc, Mutex};
 
-use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use visualizer::audio::device::list_hosts;
+use visualizer::filters::registry::{find_spatial_factory, find_temporal_factory};
+use visualizer::{
+    AudioEngine, DeviceSelection, GeneratorSettings, Preset, SourceSpec, StreamOptions, Visualizer,
+    make_frontend,
+};
 
-use visualizer::{FrontendKind, Visualizer, make_frontend};
+mod cli;
+use cli::{Cli, SourceKind};
 
-mod load_audio;
-use load_audio::load_samples_from_file;
+/// Sample rate the visualizer starts with until the audio source is opened.
+const FALLBACK_SAMPLE_RATE: u32 = 44100;
 
 fn main() {
-    let window_size = 2048;
-    let num_bins = 50;
+    let cli = Cli::parse_checked();
 
-    // Load audio samples from file provided as command line argument
-    let (samples, sample_rate) = {
-        let args: Vec<String> = std::env::args().collect();
-        let path = args.get(1).expect("file path not provided");
-        load_samples_from_file(path)
+    if cli.list_devices {
+        print_devices();
+        return;
+    }
+
+    let source = match cli.source_kind() {
+        SourceKind::File => SourceSpec::File(cli.file.clone().unwrap_or_default()),
+        SourceKind::Live => SourceSpec::Live,
+        SourceKind::Generator => SourceSpec::Generator {
+            settings: GeneratorSettings {
+                signal: cli.signal.clone(),
+                level_db: cli.level,
+            },
+            rate: cli.rate,
+        },
+    };
+    let selection = DeviceSelection {
+        host: cli.host,
+        input: cli.input_device.clone(),
+        output: cli.output_device.clone(),
+        options: StreamOptions {
+            buffer_size: cli.buffer_size,
+            sample_format: cli.sample_format,
+        },
     };
 
-    let host = cpal::default_host();
-    let device = host.default_output_device().unwrap();
-    let config = device.default_output_config().unwrap();
+    let preset = cli.preset.as_ref().map(|path| match Preset::load(path) {
+        Ok(preset) => (path.clone(), preset),
+        Err(err) => Cli::fail(&format!("cannot load '{}': {}", path.display(), err)),
+    });
 
-    // Initialize FFT data
-    let visualizer = Arc::new(Mutex::new(Visualizer::new(
-        sample_rate,
-        window_size,
-        num_bins,
-    )));
-    let visualizer_cb = visualizer.clone();
+    let visualizer = configured_visualizer(&cli, preset.as_ref().map(|(_, preset)| preset));
+    let visualizer = Arc::new(Mutex::new(visualizer));
+    let mut engine = AudioEngine::new(source, selection, visualizer);
 
-    let channels = config.channels() as usize;
-    let mut sample_pos = 0;
+    // On failure, keep the window up and show the error instead of crashing
+    match engine.start() {
+        Ok(()) => {
+            if let Some(name) = engine.device_name() {
+                println!("Using device: {}", name);
+            }
+        }
+        Err(err) => {
+            let transport = engine.transport();
+            transport.stop();
+            transport.report_error(err);
+        }
+    }
 
-    let stream = device
-        .build_output_stream(
-            &config.into(),
-            move |output: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                // Fill output buffer with audio samples
-                // Write one frame at a time (respect channel count)
-                for frame in output.chunks_mut(channels) {
-                    let sample = if sample_pos < samples.len() {
-                        samples[sample_pos]
-                    } else {
-                        0.0
-                    };
-                    // Duplicate mono sample to all channels
-                    for ch in 0..channels {
-                        frame[ch] = sample;
-                    }
-                    sample_pos += 1; // advance per frame, not per channel sample
-                }
+    let frontend = make_frontend(cli.frontend.into(), engine, preset);
+    frontend.run();
+}
 
-                // For visualization: process FFT on current window
-                if let Ok(mut vis) = visualizer_cb.lock() {
-                    let window_size_cur = vis.config.window_size;
-                    if sample_pos >= window_size_cur {
-                        let start = sample_pos - window_size_cur;
-                        let window_samples = &samples[start..sample_pos];
-                        vis.update_spectrum(&window_samples);
-                    }
-                }
-            },
-            move |err| {
-                eprintln!("Stream error: {}", err);
-            },
-            None,
-        )
-        .unwrap();
+/// Create a visualizer from the preset, if any, with the analysis options and
+/// filter chains from the command line applied on top.
+fn configured_visualizer(cli: &Cli, preset: Option<&Preset>) -> Visualizer {
+    let mut settings = preset.cloned().unwrap_or_default();
+    if let Some(window_size) = cli.window_size {
+        settings.window_size = window_size;
+    }
+    if let Some(zero_padding) = cli.zero_padding {
+        settings.zero_padding = zero_padding;
+    }
+    if let Some(bins) = cli.bins {
+        settings.num_bins = bins.into();
+    }
+    if let Some(min_freq) = cli.min_freq {
+        settings.min_freq = min_freq;
+    }
+    if cli.max_freq.is_some() {
+        settings.max_freq = cli.max_freq;
+    }
 
-    stream.play().unwrap();
+    let mut visualizer = Visualizer::new(
+        FALLBACK_SAMPLE_RATE,
+        settings.window_size,
+        settings.num_bins,
+    );
+    let config = &mut visualizer.config;
+    if let Err(err) = settings.apply(config) {
+        Cli::fail(&err.to_string());
+    }
 
-    let frontend = make_frontend(FrontendKind::Egui, visualizer);
-    frontend.run();
+    // Filter names were validated while parsing
+    let filter_manager = &mut config.filter_manager;
+    if let Some(chain) = &cli.spatial {
+        filter_manager.clear_spatial_filters();
+        for factory in chain.0.iter().filter_map(|key| find_spatial_factory(key)) {
+            filter_manager.add_spatial_from_factory(&factory);
+        }
+    }
+    if let Some(chain) = &cli.temporal {
+        filter_manager.clear_temporal_filters();
+        for factory in chain.0.iter().filter_map(|key| find_temporal_factory(key)) {
+            filter_manager.add_temporal_from_factory(&factory);
+        }
+    }
+    visualizer
+}
+
+/// Print every host with its input and output devices and their configurations.
+fn print_devices() {
+    for host in list_hosts() {
+        let default = if host.is_default { " (default)" } else { "" };
+        println!("{}{}", host.id.name(), default);
+        for (kind, devices) in [("Input", &host.inputs), ("Output", &host.outputs)] {
+            println!("  {} devices:", kind);
+            if devices.is_empty() {
+                println!("    none");
+            }
+            for device in devices {
+                let default = if device.is_default { " (default)" } else { "" };
+                println!("    {}{}", device.name, default);
+                for config in &device.configs {
+                    println!("      {}", config);
+                }
+            }
+        }
+    }
 }
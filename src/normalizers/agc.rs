@@ -0,0 +1,76 @@
+use crate::normalizers::{FrameLevels, Normalizer, NormalizerPreset, peak};
+
+/// Automatic gain control: a gain that slowly follows the one that would
+/// bring the loudest bin to `target`. Per analysis frame, the gain falls
+/// with the `attack` smoothing factor and rises with `release`, at most to
+/// `max_gain_db` above full scale.
+pub struct AgcNormalizer {
+    pub target: f32,
+    pub attack: f32,
+    pub release: f32,
+    pub max_gain_db: f32,
+    /// Current gain, relative to full scale; `None` before the first frame.
+    gain: Option<f32>,
+}
+
+impl AgcNormalizer {
+    pub fn new(target: f32, attack: f32, release: f32, max_gain_db: f32) -> Self {
+        Self {
+            target,
+            attack,
+            release,
+            max_gain_db,
+            gain: None,
+        }
+    }
+}
+
+impl AgcNormalizer {
+    /// Gain that brings the loudest of `bins` to `target`.
+    fn wanted_gain(&self, bins: &[f32], full_scale: f32) -> f32 {
+        let max_gain = 10.0_f32.powf(self.max_gain_db / 20.0);
+        (self.target * full_scale / peak(bins)).min(max_gain)
+    }
+}
+
+impl Normalizer for AgcNormalizer {
+    fn update(&mut self, bins: &[f32], levels: FrameLevels) {
+        let wanted = self.wanted_gain(bins, levels.full_scale.max(1e-12));
+        let gain = match self.gain {
+            Some(gain) => {
+                let alpha = if wanted < gain {
+                    self.attack
+                } else {
+                    self.release
+                };
+                alpha * gain + (1.0 - alpha) * wanted
+            }
+            None => wanted,
+        };
+        self.gain = Some(gain);
+    }
+
+    fn normalize(&self, bins: &mut [f32], levels: FrameLevels) {
+        let full_scale = levels.full_scale.max(1e-12);
+        let gain = self
+            .gain
+            .unwrap_or_else(|| self.wanted_gain(bins, full_scale));
+        let scale = gain / full_scale;
+        for b in bins.iter_mut() {
+            *b *= scale;
+        }
+    }
+
+    fn preset(&self) -> NormalizerPreset {
+        NormalizerPreset::Agc {
+            target: self.target,
+            attack: self.attack,
+            release: self.release,
+            max_gain_db: self.max_gain_db,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.gain = None;
+    }
+}
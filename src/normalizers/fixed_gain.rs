@@ -0,0 +1,27 @@
+use crate::normalizers::{FrameLevels, Normalizer, NormalizerPreset};
+
+/// Constant gain: at 0 dB a full-scale sine fills its bar.
+pub struct FixedGainNormalizer {
+    pub gain_db: f32,
+}
+
+impl FixedGainNormalizer {
+    pub fn new(gain_db: f32) -> Self {
+        Self { gain_db }
+    }
+}
+
+impl Normalizer for FixedGainNormalizer {
+    fn normalize(&self, bins: &mut [f32], levels: FrameLevels) {
+        let scale = 10.0_f32.powf(self.gain_db / 20.0) / levels.full_scale.max(1e-12);
+        for b in bins.iter_mut() {
+            *b *= scale;
+        }
+    }
+
+    fn preset(&self) -> NormalizerPreset {
+        NormalizerPreset::FixedGain {
+            gain_db: self.gain_db,
+        }
+    }
+}
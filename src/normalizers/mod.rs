@@ -0,0 +1,54 @@
+pub mod agc;
+pub mod fixed_gain;
+pub mod peak;
+pub mod percentile;
+pub mod preset;
+pub mod rms_loudness;
+
+pub use agc::AgcNormalizer;
+pub use fixed_gain::FixedGainNormalizer;
+pub use peak::PeakNormalizer;
+pub use percentile::PercentileNormalizer;
+pub use preset::NormalizerPreset;
+pub use rms_loudness::RmsLoudnessNormalizer;
+
+use crate::frontend::egui_frontend::UiComponent;
+
+/// Levels of the analyzed frame that normalizers may scale by.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameLevels {
+    /// RMS of the analyzed window.
+    pub window_rms: f32,
+    /// Bin magnitude of a full-scale sine.
+    pub full_scale: f32,
+}
+
+/// Maps bin magnitudes to bar heights; results are clamped to 0..=1
+/// afterwards. State that follows the signal advances once per analysis
+/// frame in `update`, so it does not depend on how often frames are drawn.
+pub trait Normalizer: Send + Sync + UiComponent {
+    /// Advance the state by one analysis frame of bin magnitudes.
+    fn update(&mut self, _bins: &[f32], _levels: FrameLevels) {}
+
+    fn normalize(&self, bins: &mut [f32], levels: FrameLevels);
+
+    /// Current parameters, for saving to a preset.
+    fn preset(&self) -> NormalizerPreset;
+
+    /// Forget state carried over from earlier frames.
+    fn reset(&mut self) {}
+}
+
+impl<T> From<T> for Box<dyn Normalizer>
+where
+    T: Normalizer + 'static,
+{
+    fn from(value: T) -> Self {
+        Box::new(value)
+    }
+}
+
+/// Largest magnitude in `bins`, at least a tiny positive value.
+fn peak(bins: &[f32]) -> f32 {
+    bins.iter().fold(0.0_f32, |m, v| m.max(*v)).max(1e-12)
+}
@@ -0,0 +1,27 @@
+use crate::normalizers::{FrameLevels, Normalizer, NormalizerPreset, peak};
+
+/// Scales every frame so its loudest bin reaches `target`.
+pub struct PeakNormalizer {
+    pub target: f32,
+}
+
+impl PeakNormalizer {
+    pub fn new(target: f32) -> Self {
+        Self { target }
+    }
+}
+
+impl Normalizer for PeakNormalizer {
+    fn normalize(&self, bins: &mut [f32], _levels: FrameLevels) {
+        let scale = self.target / peak(bins);
+        for b in bins.iter_mut() {
+            *b *= scale;
+        }
+    }
+
+    fn preset(&self) -> NormalizerPreset {
+        NormalizerPreset::Peak {
+            target: self.target,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+
+use crate::normalizers::{FrameLevels, Normalizer, NormalizerPreset, peak};
+
+/// Auto-range: the `percentile` of the frame peaks over the last `frames`
+/// analysis frames fills the bar, so rare transients clip instead of
+/// shrinking the display.
+pub struct PercentileNormalizer {
+    /// Fraction of frame peaks that fit below full height, from 0 to 1.
+    pub percentile: f32,
+    pub frames: usize,
+    /// Frame peaks, oldest first.
+    peaks: VecDeque<f32>,
+    /// The same peaks in ascending order.
+    sorted: Vec<f32>,
+}
+
+impl PercentileNormalizer {
+    pub fn new(percentile: f32, frames: usize) -> Self {
+        Self {
+            percentile,
+            frames,
+            peaks: VecDeque::new(),
+            sorted: Vec::new(),
+        }
+    }
+}
+
+impl Normalizer for PercentileNormalizer {
+    fn update(&mut self, bins: &[f32], _levels: FrameLevels) {
+        let new = peak(bins);
+        self.peaks.push_back(new);
+        let at = self.sorted.partition_point(|&p| p < new);
+        self.sorted.insert(at, new);
+        while self.peaks.len() > self.frames.max(1) {
+            if let Some(old) = self.peaks.pop_front() {
+                let at = self.sorted.partition_point(|&p| p < old);
+                self.sorted.remove(at);
+            }
+        }
+    }
+
+    fn normalize(&self, bins: &mut [f32], _levels: FrameLevels) {
+        let level = if self.sorted.is_empty() {
+            peak(bins)
+        } else {
+            let rank = self.percentile.clamp(0.0, 1.0) * (self.sorted.len() - 1) as f32;
+            self.sorted[rank.round() as usize]
+        };
+        let scale = 1.0 / level;
+        for b in bins.iter_mut() {
+            *b *= scale;
+        }
+    }
+
+    fn preset(&self) -> NormalizerPreset {
+        NormalizerPreset::Percentile {
+            percentile: self.percentile,
+            frames: self.frames,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.peaks.clear();
+        self.sorted.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_matches_a_full_sort() {
+        let levels = FrameLevels {
+            window_rms: 0.0,
+            full_scale: 1.0,
+        };
+        let mut normalizer = PercentileNormalizer::new(0.75, 7);
+        for i in 0..50_u32 {
+            let value = ((i * 37) % 23) as f32 + 1.0;
+            normalizer.update(&[value], levels);
+            if i == 30 {
+                normalizer.frames = 5;
+            }
+            let mut expected: Vec<f32> = normalizer.peaks.iter().copied().collect();
+            expected.sort_by(f32::total_cmp);
+            assert_eq!(normalizer.sorted, expected);
+        }
+        assert_eq!(normalizer.peaks.len(), 5);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+use crate::normalizers::{
+    AgcNormalizer, FixedGainNormalizer, Normalizer, PeakNormalizer, PercentileNormalizer,
+    RmsLoudnessNormalizer,
+};
+
+/// Serializable description of a normalizer and its parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum NormalizerPreset {
+    RmsLoudness {
+        reference: f32,
+        floor: f32,
+        gamma: f32,
+    },
+    Peak {
+        target: f32,
+    },
+    Agc {
+        target: f32,
+        attack: f32,
+        release: f32,
+        max_gain_db: f32,
+    },
+    FixedGain {
+        gain_db: f32,
+    },
+    Percentile {
+        percentile: f32,
+        frames: usize,
+    },
+}
+
+impl Default for NormalizerPreset {
+    fn default() -> Self {
+        RmsLoudnessNormalizer::default().preset()
+    }
+}
+
+impl NormalizerPreset {
+    /// One of each kind with default parameters.
+    pub fn options() -> [NormalizerPreset; 5] {
+        [
+            NormalizerPreset::default(),
+            NormalizerPreset::Peak { target: 0.9 },
+            NormalizerPreset::Agc {
+                target: 0.8,
+                attack: 0.5,
+                release: 0.99,
+                max_gain_db: 60.0,
+            },
+            NormalizerPreset::FixedGain { gain_db: 0.0 },
+            NormalizerPreset::Percentile {
+                percentile: 0.95,
+                frames: 200,
+            },
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NormalizerPreset::RmsLoudness { .. } => "RMS loudness",
+            NormalizerPreset::Peak { .. } => "Peak",
+            NormalizerPreset::Agc { .. } => "Automatic gain",
+            NormalizerPreset::FixedGain { .. } => "Fixed gain",
+            NormalizerPreset::Percentile { .. } => "Percentile",
+        }
+    }
+
+    /// True if both describe the same normalizer, ignoring parameters.
+    pub fn same_kind(&self, other: &NormalizerPreset) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Create the described normalizer.
+    pub fn build(&self) -> Box<dyn Normalizer> {
+        match *self {
+            NormalizerPreset::RmsLoudness {
+                reference,
+                floor,
+                gamma,
+            } => RmsLoudnessNormalizer::new(reference, floor, gamma).into(),
+            NormalizerPreset::Peak { target } => PeakNormalizer::new(target).into(),
+            NormalizerPreset::Agc {
+                target,
+                attack,
+                release,
+                max_gain_db,
+            } => AgcNormalizer::new(target, attack, release, max_gain_db).into(),
+            NormalizerPreset::FixedGain { gain_db } => FixedGainNormalizer::new(gain_db).into(),
+            NormalizerPreset::Percentile { percentile, frames } => {
+                PercentileNormalizer::new(percentile, frames).into()
+            }
+        }
+    }
+
+    /// Check that the parameters are in the range the normalizer supports.
+    pub fn validate(&self) -> Result<(), String> {
+        let factor = 0.0..=1.0;
+        match *self {
+            NormalizerPreset::RmsLoudness {
+                reference,
+                floor,
+                gamma,
+            } => {
+                if !(floor >= 0.0 && reference > floor && gamma > 0.0) {
+                    return Err(
+                        "rms loudness needs 0 <= floor < reference and a positive gamma"
+                            .to_string(),
+                    );
+                }
+            }
+            NormalizerPreset::Peak { target } => {
+                if !(target > 0.0 && target <= 1.0) {
+                    return Err(format!("peak target {} is not in (0, 1]", target));
+                }
+            }
+            NormalizerPreset::Agc {
+                target,
+                attack,
+                release,
+                max_gain_db,
+            } => {
+                if !(target > 0.0 && target <= 1.0) {
+                    return Err(format!("agc target {} is not in (0, 1]", target));
+                }
+                if !(factor.contains(&attack) && factor.contains(&release)) {
+                    return Err(format!(
+                        "agc attack {} and release {} must be between 0 and 1",
+                        attack, release
+                    ));
+                }
+                if !(0.0..=120.0).contains(&max_gain_db) {
+                    return Err(format!(
+                        "agc max gain {} dB is not between 0 and 120",
+                        max_gain_db
+                    ));
+                }
+            }
+            NormalizerPreset::FixedGain { gain_db } => {
+                if !(-60.0..=120.0).contains(&gain_db) {
+                    return Err(format!("gain {} dB is not between -60 and 120", gain_db));
+                }
+            }
+            NormalizerPreset::Percentile { percentile, frames } => {
+                if !factor.contains(&percentile) {
+                    return Err(format!("percentile {} is not between 0 and 1", percentile));
+                }
+                if !(1..=10_000).contains(&frames) {
+                    return Err(format!("{} frames is not between 1 and 10000", frames));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+use crate::normalizers::{FrameLevels, Normalizer, NormalizerPreset, peak};
+
+/// Scales the loudest bin to a height set by the window RMS.
+/// Levels at or below `floor` are silent, `reference` reaches full height and
+/// `gamma` shapes the curve in between.
+pub struct RmsLoudnessNormalizer {
+    pub reference: f32,
+    pub floor: f32,
+    pub gamma: f32,
+}
+
+impl RmsLoudnessNormalizer {
+    pub fn new(reference: f32, floor: f32, gamma: f32) -> Self {
+        Self {
+            reference,
+            floor,
+            gamma,
+        }
+    }
+}
+
+impl Default for RmsLoudnessNormalizer {
+    fn default() -> Self {
+        Self::new(0.6, 0.01, 0.7)
+    }
+}
+
+impl Normalizer for RmsLoudnessNormalizer {
+    fn normalize(&self, bins: &mut [f32], levels: FrameLevels) {
+        let loudness = if levels.window_rms <= self.floor {
+            0.0
+        } else {
+            let norm = (levels.window_rms - self.floor) / (self.reference - self.floor);
+            norm.clamp(0.0, 1.0).powf(self.gamma)
+        };
+
+        let scale = loudness.max(0.001) / peak(bins);
+        for b in bins.iter_mut() {
+            *b *= scale;
+        }
+    }
+
+    fn preset(&self) -> NormalizerPreset {
+        NormalizerPreset::RmsLoudness {
+            reference: self.reference,
+            floor: self.floor,
+            gamma: self.gamma,
+        }
+    }
+}
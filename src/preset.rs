@@ -0,0 +1,535 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::AnalysisMethod;
+use crate::binning::{BinAggregation, BinInterpolation};
+use crate::error::{ConfigError, PresetError};
+use crate::filters::{SpatialFilterPreset, TemporalFilterPreset};
+use crate::normalizers::NormalizerPreset;
+use crate::scale::FrequencyScale;
+use crate::spectrogram::{Colormap, MAX_SPECTROGRAM_DEPTH};
+use crate::visualizer::{
+    MAX_BINS, MAX_FFT_SIZE, MAX_OVERLAP, MIN_WINDOW_SIZE, OutputMode, VisualizerConfig,
+};
+use crate::window::WindowFunction;
+
+/// An sRGB color with alpha, written as `#rrggbb` or `#rrggbbaa`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Color(pub [u8; 4]);
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color([r, g, b, 255])
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [r, g, b, a] = self.0;
+        write!(f, "#{:02x}{:02x}{:02x}", r, g, b)?;
+        if a != 255 {
+            write!(f, "{:02x}", a)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        let invalid = || format!("'{}' is not a #rrggbb or #rrggbbaa color", s);
+        if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let mut rgba = [255; 4];
+        for (i, channel) in rgba.iter_mut().enumerate().take(hex.len() / 2) {
+            *channel = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).map_err(|_| invalid())?;
+        }
+        Ok(Color(rgba))
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+/// Bar and spectrogram colors of the spectrum display.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    /// Bars of the mono spectrum or the left channel.
+    pub bars: Color,
+    /// Bars of the right channel in stereo mode.
+    pub right: Color,
+    /// Colors of the spectrogram levels.
+    pub colormap: Colormap,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        Self {
+            bars: Color::rgb(0, 0, 139),
+            right: Color::rgb(139, 0, 0),
+            colormap: Colormap::default(),
+        }
+    }
+}
+
+/// Analysis, filter and display settings that can be saved to and loaded
+/// from TOML or JSON. Missing fields take their default values.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preset {
+    /// Analysis window length in samples.
+    pub window_size: usize,
+    /// Zero-padding factor of the FFT, a power of two.
+    pub zero_padding: usize,
+    pub window: WindowFunction,
+    /// Fraction of each window shared with the previous one.
+    pub overlap: f32,
+    pub num_bins: usize,
+    pub min_freq: f32,
+    /// Highest displayed frequency; Nyquist when omitted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_freq: Option<f32>,
+    /// Spacing of the bins; fractional-octave and note scales ignore `num_bins`.
+    #[serde(alias = "layout")]
+    pub scale: FrequencyScale,
+    pub analysis: AnalysisMethod,
+    /// Fills bins that no FFT line falls into.
+    pub interpolation: BinInterpolation,
+    /// Combines the FFT lines within a bin.
+    pub aggregation: BinAggregation,
+    /// Maps bin magnitudes to bar heights in relative output mode.
+    pub normalizer: NormalizerPreset,
+    pub output: OutputMode,
+    /// Frames kept for the spectrogram.
+    pub spectrogram_depth: usize,
+    /// Spatial filters in processing order.
+    pub spatial_filters: Vec<SpatialFilterPreset>,
+    /// Temporal filters in processing order.
+    pub temporal_filters: Vec<TemporalFilterPreset>,
+    pub colors: Colors,
+}
+
+impl Default for Preset {
+    /// The settings a new `Visualizer` starts with.
+    fn default() -> Self {
+        Self {
+            window_size: 2048,
+            zero_padding: 1,
+            window: WindowFunction::default(),
+            overlap: 0.75,
+            num_bins: 50,
+            min_freq: 20.0,
+            max_freq: None,
+            scale: FrequencyScale::default(),
+            analysis: AnalysisMethod::default(),
+            interpolation: BinInterpolation::default(),
+            aggregation: BinAggregation::default(),
+            normalizer: NormalizerPreset::default(),
+            output: OutputMode::default(),
+            spectrogram_depth: 256,
+            spatial_filters: vec![SpatialFilterPreset::Gaussian {
+                sigma: 3.0,
+                radius: 2,
+                passes: 3,
+                enabled: true,
+            }],
+            temporal_filters: vec![TemporalFilterPreset::AttackRelease {
+                attack: 0.7,
+                release: 0.9,
+                enabled: true,
+            }],
+            colors: Colors::default(),
+        }
+    }
+}
+
+impl Preset {
+    /// Capture the current settings of `config` with default colors.
+    pub fn capture(config: &VisualizerConfig) -> Self {
+        let (min_freq, max_freq) = config.frequency_range();
+        let at_nyquist = max_freq >= config.sample_rate as f32 / 2.0 - 1.0;
+        Self {
+            window_size: config.window_size(),
+            zero_padding: config.zero_padding(),
+            window: config.window_function(),
+            overlap: config.overlap(),
+            num_bins: config.num_bins(),
+            min_freq,
+            max_freq: (!at_nyquist).then_some(max_freq),
+            scale: config.scale(),
+            analysis: config.analysis(),
+            interpolation: config.interpolation,
+            aggregation: config.aggregation,
+            normalizer: config.normalizer_preset(),
+            output: config.output,
+            spectrogram_depth: config.spectrogram_depth(),
+            spatial_filters: config.filter_manager.spatial_presets(),
+            temporal_filters: config.filter_manager.temporal_presets(),
+            colors: Colors::default(),
+        }
+    }
+
+    /// Apply the analysis and filter settings to `config`, replacing both
+    /// filter chains. Temporal filters keep their state if the layout keeps
+    /// its number of bins, the scale and analysis method stay the same and the
+    /// chain has a filter of the same type; the normalizer keeps its state if
+    /// it is unchanged. Colors are left to the frontend. An invalid preset
+    /// leaves `config` unchanged.
+    pub fn apply(&self, config: &mut VisualizerConfig) -> Result<(), PresetError> {
+        self.validate()?;
+        let same_kind = self.scale == config.scale() && self.analysis == config.analysis();
+        let old_bins = config.layout.num_bins();
+        let states = config.filter_manager.temporal_states();
+
+        config.set_window(self.window_size, self.zero_padding)?;
+        config.set_window_function(self.window);
+        config.set_overlap(self.overlap)?;
+        config.set_num_bins(self.num_bins)?;
+        config.set_scale(self.scale);
+        config.set_analysis(self.analysis)?;
+        config.set_frequency_range(self.min_freq, self.max_freq.unwrap_or(f32::MAX))?;
+        config.interpolation = self.interpolation;
+        config.aggregation = self.aggregation;
+        if self.normalizer != config.normalizer_preset() {
+            config.set_normalizer(self.normalizer.build());
+        }
+        config.output = self.output;
+        config.set_spectrogram_depth(self.spectrogram_depth)?;
+
+        let filter_manager = &mut config.filter_manager;
+        filter_manager.clear_spatial_filters();
+        for filter in &self.spatial_filters {
+            filter.add_to(filter_manager);
+        }
+        filter_manager.clear_temporal_filters();
+        for filter in &self.temporal_filters {
+            filter.add_to(filter_manager);
+        }
+        // Fractional-octave, note and constant-Q layouts ignore `num_bins`, so
+        // compare the bins the layout actually has
+        if same_kind && config.layout.num_bins() == old_bins {
+            filter_manager.restore_temporal_states(states);
+        }
+        Ok(())
+    }
+
+    /// Check the values against the limits the visualizer supports.
+    pub fn validate(&self) -> Result<(), PresetError> {
+        let invalid = |message: String| Err(PresetError::Invalid(message));
+        if !self.zero_padding.is_power_of_two() {
+            return Err(ConfigError::ZeroPadding(self.zero_padding).into());
+        }
+        let fft_size = self
+            .window_size
+            .next_power_of_two()
+            .saturating_mul(self.zero_padding);
+        if self.window_size < MIN_WINDOW_SIZE || fft_size > MAX_FFT_SIZE {
+            return Err(ConfigError::WindowSize {
+                window_size: self.window_size,
+                zero_padding: self.zero_padding,
+            }
+            .into());
+        }
+        if let WindowFunction::Kaiser { beta } = self.window
+            && !(0.0..=50.0).contains(&beta)
+        {
+            return invalid(format!("kaiser beta {} is not between 0 and 50", beta));
+        }
+        if !(0.0..=MAX_OVERLAP).contains(&self.overlap) {
+            return Err(ConfigError::Overlap(self.overlap).into());
+        }
+        self.analysis.validate()?;
+        if !(1..=MAX_BINS).contains(&self.num_bins) {
+            return Err(ConfigError::NumBins(self.num_bins).into());
+        }
+        if let FrequencyScale::Notes {
+            a4,
+            lowest,
+            highest,
+        } = self.scale
+        {
+            if !(400.0..=480.0).contains(&a4) {
+                return invalid(format!("a4 {} Hz is not between 400 and 480", a4));
+            }
+            if !(lowest <= highest && highest <= 127) {
+                return invalid(format!(
+                    "note range {}..={} is not within MIDI notes 0..=127",
+                    lowest, highest
+                ));
+            }
+        }
+        if !(self.min_freq.is_finite() && self.min_freq > 0.0) {
+            return invalid(format!("min_freq {} is not positive", self.min_freq));
+        }
+        if let Some(max_freq) = self.max_freq
+            && !(max_freq.is_finite() && max_freq > self.min_freq)
+        {
+            return invalid(format!("max_freq {} is not above min_freq", max_freq));
+        }
+        self.normalizer.validate().map_err(PresetError::Invalid)?;
+        if let OutputMode::Decibels {
+            floor_db,
+            ceiling_db,
+        } = self.output
+            && !(floor_db.is_finite() && ceiling_db.is_finite() && floor_db < ceiling_db)
+        {
+            return invalid(format!(
+                "dB floor {} is not below the ceiling {}",
+                floor_db, ceiling_db
+            ));
+        }
+        if !(1..=MAX_SPECTROGRAM_DEPTH).contains(&self.spectrogram_depth) {
+            return Err(ConfigError::SpectrogramDepth(self.spectrogram_depth).into());
+        }
+        for filter in &self.spatial_filters {
+            filter.validate().map_err(PresetError::Invalid)?;
+        }
+        for filter in &self.temporal_filters {
+            filter.validate().map_err(PresetError::Invalid)?;
+        }
+        Ok(())
+    }
+
+    /// Parse a preset from TOML, or JSON if the path ends in `.json`.
+    pub fn load(path: &Path) -> Result<Self, PresetError> {
+        let text = std::fs::read_to_string(path)?;
+        let preset: Preset = if is_json(path) {
+            serde_json::from_str(&text)?
+        } else {
+            toml::from_str(&text)?
+        };
+        preset.validate()?;
+        Ok(preset)
+    }
+
+    /// Write the preset as TOML, or JSON if the path ends in `.json`.
+    pub fn save(&self, path: &Path) -> Result<(), PresetError> {
+        let text = if is_json(path) {
+            serde_json::to_string_pretty(self)?
+        } else {
+            toml::to_string_pretty(self)?
+        };
+        std::fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+/// Watches a preset file by polling its modification time.
+pub struct PresetWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl PresetWatcher {
+    /// Watch `path`; the version currently on disk counts as seen.
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modified_time(&path);
+        Self { path, modified }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Treat the version currently on disk as seen, e.g. after saving to it.
+    pub fn mark_seen(&mut self) {
+        self.modified = modified_time(&self.path);
+    }
+
+    /// Load the preset if the file changed since it was last seen.
+    /// Returns `None` while the file is unchanged or missing.
+    pub fn poll(&mut self) -> Option<Result<Preset, PresetError>> {
+        let modified = modified_time(&self.path)?;
+        if self.modified == Some(modified) {
+            return None;
+        }
+        self.modified = Some(modified);
+        Some(Preset::load(&self.path))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// True for files with a `.toml` or `.json` extension.
+pub fn is_preset_file(path: &Path) -> bool {
+    is_json(path)
+        || path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A preset that differs from the default in every section.
+    fn custom() -> Preset {
+        Preset {
+            window_size: 3000,
+            zero_padding: 4,
+            window: WindowFunction::Kaiser { beta: 8.5 },
+            overlap: 0.5,
+            num_bins: 72,
+            min_freq: 40.0,
+            max_freq: Some(12000.0),
+            scale: FrequencyScale::Notes {
+                a4: 442.0,
+                lowest: 21,
+                highest: 108,
+            },
+            analysis: AnalysisMethod::MultiResolution {
+                bass_window: 8192,
+                mid_window: 2048,
+                treble_window: 512,
+                low_crossover: 250.0,
+                high_crossover: 2000.0,
+            },
+            interpolation: BinInterpolation::default(),
+            aggregation: BinAggregation::Max,
+            normalizer: NormalizerPreset::Percentile {
+                percentile: 0.95,
+                frames: 300,
+            },
+            output: OutputMode::Decibels {
+                floor_db: -80.0,
+                ceiling_db: -6.0,
+            },
+            spectrogram_depth: 512,
+            spatial_filters: Vec::new(),
+            temporal_filters: vec![TemporalFilterPreset::AttackRelease {
+                attack: 0.5,
+                release: 0.25,
+                enabled: false,
+            }],
+            colors: Colors {
+                bars: Color::rgb(1, 2, 3),
+                right: Color([4, 5, 6, 128]),
+                colormap: Colormap::Magma,
+            },
+        }
+    }
+
+    fn round_trip(extension: &str) {
+        let path = std::env::temp_dir().join(format!(
+            "visualizer-preset-{}.{}",
+            std::process::id(),
+            extension
+        ));
+        let preset = custom();
+        preset.save(&path).unwrap();
+        let loaded = Preset::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.unwrap(), preset);
+    }
+
+    #[test]
+    fn toml_round_trip() {
+        round_trip("toml");
+    }
+
+    #[test]
+    fn json_round_trip() {
+        round_trip("json");
+    }
+
+    #[test]
+    fn missing_fields_take_defaults() {
+        let preset: Preset = toml::from_str("num_bins = 30").unwrap();
+        assert_eq!(preset.num_bins, 30);
+        assert_eq!(preset.window_size, Preset::default().window_size);
+        assert!(preset.validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_out_of_range_values() {
+        let rejects = |change: fn(&mut Preset)| {
+            let mut preset = Preset::default();
+            change(&mut preset);
+            preset.validate().unwrap_err()
+        };
+        assert!(matches!(
+            rejects(|p| p.zero_padding = 3),
+            PresetError::Config(ConfigError::ZeroPadding(3))
+        ));
+        assert!(matches!(
+            rejects(|p| p.window_size = MIN_WINDOW_SIZE - 1),
+            PresetError::Config(ConfigError::WindowSize { .. })
+        ));
+        assert!(matches!(
+            rejects(|p| p.window_size = MAX_FFT_SIZE + 1),
+            PresetError::Config(ConfigError::WindowSize { .. })
+        ));
+        assert!(matches!(
+            rejects(|p| p.overlap = 0.99),
+            PresetError::Config(ConfigError::Overlap(_))
+        ));
+        assert!(matches!(
+            rejects(|p| p.num_bins = 0),
+            PresetError::Config(ConfigError::NumBins(0))
+        ));
+        assert!(matches!(
+            rejects(|p| p.spectrogram_depth = MAX_SPECTROGRAM_DEPTH + 1),
+            PresetError::Config(ConfigError::SpectrogramDepth(_))
+        ));
+        assert!(matches!(
+            rejects(|p| p.window = WindowFunction::Kaiser { beta: 60.0 }),
+            PresetError::Invalid(_)
+        ));
+        assert!(matches!(
+            rejects(|p| p.max_freq = Some(10.0)),
+            PresetError::Invalid(_)
+        ));
+        assert!(matches!(
+            rejects(|p| p.output = OutputMode::Decibels {
+                floor_db: 0.0,
+                ceiling_db: -10.0
+            }),
+            PresetError::Invalid(_)
+        ));
+        assert!(matches!(
+            rejects(|p| p.scale = FrequencyScale::Notes {
+                a4: 440.0,
+                lowest: 60,
+                highest: 130
+            }),
+            PresetError::Invalid(_)
+        ));
+    }
+
+    #[test]
+    fn invalid_preset_leaves_config_unchanged() {
+        let mut visualizer = crate::Visualizer::new(48000, 2048, 50);
+        let before = Preset::capture(&visualizer.config);
+        let mut preset = custom();
+        preset.overlap = 2.0;
+        assert!(preset.apply(&mut visualizer.config).is_err());
+        assert_eq!(Preset::capture(&visualizer.config), before);
+    }
+}
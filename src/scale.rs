@@ -0,0 +1,323 @@
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::visualizer::MAX_BINS;
+
+/// Octave ratio of the base-ten system in IEC 61260.
+const OCTAVE_RATIO: f32 = 1.995_262_3; // 10^(3/10)
+
+/// Reference frequency of the fractional-octave bands.
+const REFERENCE_FREQ: f32 = 1000.0;
+
+/// MIDI note number of A4.
+const A4_NOTE: f32 = 69.0;
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// How frequencies are spread over the displayed bins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FrequencyScale {
+    Linear,
+    #[default]
+    Log,
+    /// Mel scale (O'Shaughnessy).
+    Mel,
+    /// Bark scale (Traunmüller).
+    Bark,
+    /// Equivalent rectangular bandwidth rate (Glasberg & Moore).
+    Erb,
+    /// Fractional-octave bands per IEC 61260. The number of bins follows
+    /// from the frequency range.
+    Octave,
+    ThirdOctave,
+    SixthOctave,
+    TwelfthOctave,
+    /// One bin per equal-tempered semitone from MIDI note `lowest` to
+    /// `highest`, tuned to `a4` Hz, with edges at the quarter tones between.
+    Notes {
+        a4: f32,
+        lowest: u8,
+        highest: u8,
+    },
+}
+
+impl FrequencyScale {
+    /// One of each kind, with the notes of an 88-key piano at A4 = 440 Hz.
+    pub fn options() -> [FrequencyScale; 10] {
+        [
+            FrequencyScale::Linear,
+            FrequencyScale::Log,
+            FrequencyScale::Mel,
+            FrequencyScale::Bark,
+            FrequencyScale::Erb,
+            FrequencyScale::Octave,
+            FrequencyScale::ThirdOctave,
+            FrequencyScale::SixthOctave,
+            FrequencyScale::TwelfthOctave,
+            FrequencyScale::Notes {
+                a4: 440.0,
+                lowest: 21,
+                highest: 108,
+            },
+        ]
+    }
+
+    /// True if both are the same scale, ignoring parameters.
+    pub fn same_kind(&self, other: &FrequencyScale) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FrequencyScale::Linear => "Linear",
+            FrequencyScale::Log => "Log",
+            FrequencyScale::Mel => "Mel",
+            FrequencyScale::Bark => "Bark",
+            FrequencyScale::Erb => "ERB",
+            FrequencyScale::Octave => "1/1 octave",
+            FrequencyScale::ThirdOctave => "1/3 octave",
+            FrequencyScale::SixthOctave => "1/6 octave",
+            FrequencyScale::TwelfthOctave => "1/12 octave",
+            FrequencyScale::Notes { .. } => "Notes",
+        }
+    }
+
+    /// Bands per octave of the fractional-octave scales.
+    pub fn bands_per_octave(&self) -> Option<u32> {
+        match self {
+            FrequencyScale::Octave => Some(1),
+            FrequencyScale::ThirdOctave => Some(3),
+            FrequencyScale::SixthOctave => Some(6),
+            FrequencyScale::TwelfthOctave => Some(12),
+            _ => None,
+        }
+    }
+
+    /// False for scales whose bands are fixed, which ignore the requested
+    /// number of bins.
+    pub fn uses_num_bins(&self) -> bool {
+        self.bands_per_octave().is_none() && self.note_range().is_none()
+    }
+
+    /// MIDI notes of the `Notes` scale, lowest first.
+    pub fn note_range(&self) -> Option<RangeInclusive<u8>> {
+        match *self {
+            FrequencyScale::Notes {
+                lowest, highest, ..
+            } => {
+                let (lowest, highest) = (lowest.min(127), highest.min(127));
+                Some(lowest.min(highest)..=lowest.max(highest))
+            }
+            _ => None,
+        }
+    }
+
+    /// Position of `freq` Hz on the scale's axis.
+    pub fn to_scale(&self, freq: f32) -> f32 {
+        match self {
+            FrequencyScale::Linear => freq,
+            FrequencyScale::Mel => 2595.0 * (1.0 + freq / 700.0).log10(),
+            FrequencyScale::Bark => 26.81 * freq / (1960.0 + freq) - 0.53,
+            FrequencyScale::Erb => 21.4 * (1.0 + 0.00437 * freq).log10(),
+            _ => freq.max(f32::MIN_POSITIVE).ln(),
+        }
+    }
+
+    /// Frequency in Hz at `value` on the scale's axis.
+    pub fn from_scale(&self, value: f32) -> f32 {
+        match self {
+            FrequencyScale::Linear => value,
+            FrequencyScale::Mel => 700.0 * (10.0_f32.powf(value / 2595.0) - 1.0),
+            FrequencyScale::Bark => 1960.0 * (value + 0.53) / (26.28 - value),
+            FrequencyScale::Erb => (10.0_f32.powf(value / 21.4) - 1.0) / 0.00437,
+            _ => value.exp(),
+        }
+    }
+
+    /// Band edges in Hz, one more than the number of bands. Continuous scales
+    /// split `min_freq`..`max_freq` into `num_bins` equal steps on their axis;
+    /// fractional-octave scales use the standard bands whose center lies in
+    /// the range, and the `Notes` scale its own note range.
+    pub fn band_edges(&self, num_bins: usize, min_freq: f32, max_freq: f32) -> Vec<f32> {
+        if let Some(bands) = self.bands_per_octave() {
+            return octave_band_edges(bands, min_freq, max_freq);
+        }
+        if let (FrequencyScale::Notes { a4, .. }, Some(notes)) = (self, self.note_range()) {
+            let first = *notes.start() as f32 - 0.5;
+            return (0..=notes.len())
+                .map(|i| pitch_frequency(*a4, first + i as f32))
+                .collect();
+        }
+        let num_bins = num_bins.max(1);
+        let lo = self.to_scale(min_freq);
+        let hi = self.to_scale(max_freq);
+        (0..=num_bins)
+            .map(|i| match i {
+                // Exact limits, free of rounding in the round trip
+                0 => min_freq,
+                i if i == num_bins => max_freq,
+                i => self.from_scale(lo + (hi - lo) * i as f32 / num_bins as f32),
+            })
+            .collect()
+    }
+}
+
+/// Edges of the 1/`bands` octave bands whose center lies within
+/// `min_freq`..=`max_freq`, or of the single band nearest to the range.
+fn octave_band_edges(bands: u32, min_freq: f32, max_freq: f32) -> Vec<f32> {
+    let b = bands as f32;
+    // With an even number of bands per octave, the centers sit half a band
+    // off the reference frequency
+    let offset = if bands.is_multiple_of(2) { 0.5 } else { 0.0 };
+    let index = |freq: f32| b * (freq / REFERENCE_FREQ).ln() / OCTAVE_RATIO.ln() - offset;
+    let center = |x: f32| REFERENCE_FREQ * OCTAVE_RATIO.powf((x + offset) / b);
+    let half_band = OCTAVE_RATIO.powf(1.0 / (2.0 * b));
+
+    let mut first = index(min_freq).ceil();
+    let mut last = index(max_freq).floor();
+    if first > last {
+        first = index((min_freq * max_freq).sqrt()).round();
+        last = first;
+    }
+    let last = last.min(first + (MAX_BINS - 1) as f32);
+
+    let mut edges = vec![center(first) / half_band];
+    let mut x = first;
+    while x <= last {
+        edges.push(center(x) * half_band);
+        x += 1.0;
+    }
+    edges
+}
+
+/// Frequency of the (fractional) MIDI note `note` with A4 at `a4` Hz.
+pub fn pitch_frequency(a4: f32, note: f32) -> f32 {
+    a4 * 2.0_f32.powf((note - A4_NOTE) / 12.0)
+}
+
+/// Scientific pitch name of a MIDI note, e.g. `A4` or `C#3`.
+pub fn note_name(note: u8) -> String {
+    let octave = note as i32 / 12 - 1;
+    format!("{}{}", NOTE_NAMES[note as usize % 12], octave)
+}
+
+/// True for notes on the black keys of a piano.
+pub fn is_black_key(note: u8) -> bool {
+    NOTE_NAMES[note as usize % 12].ends_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32, tolerance: f32) -> bool {
+        (a - b).abs() <= tolerance * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn continuous_scales_round_trip() {
+        for scale in [
+            FrequencyScale::Linear,
+            FrequencyScale::Log,
+            FrequencyScale::Mel,
+            FrequencyScale::Bark,
+            FrequencyScale::Erb,
+        ] {
+            for freq in [20.0, 100.0, 440.0, 1000.0, 5000.0, 20000.0] {
+                let back = scale.from_scale(scale.to_scale(freq));
+                assert!(close(back, freq, 1e-4), "{scale:?}: {freq} -> {back}");
+            }
+        }
+    }
+
+    #[test]
+    fn known_scale_values() {
+        // 1000 Hz is 1000 mel by construction of the O'Shaughnessy formula
+        assert!(close(FrequencyScale::Mel.to_scale(1000.0), 1000.0, 1e-3));
+        assert!(close(FrequencyScale::Bark.to_scale(1000.0), 8.527, 1e-3));
+        assert!(close(FrequencyScale::Erb.to_scale(1000.0), 15.62, 1e-3));
+    }
+
+    #[test]
+    fn continuous_edges_are_even_on_the_axis() {
+        for scale in [
+            FrequencyScale::Mel,
+            FrequencyScale::Bark,
+            FrequencyScale::Erb,
+        ] {
+            let edges = scale.band_edges(40, 30.0, 16000.0);
+            assert_eq!(edges.len(), 41);
+            assert_eq!((edges[0], edges[40]), (30.0, 16000.0));
+            let step = (scale.to_scale(16000.0) - scale.to_scale(30.0)) / 40.0;
+            for pair in edges.windows(2) {
+                let width = scale.to_scale(pair[1]) - scale.to_scale(pair[0]);
+                assert!(close(width, step, 1e-3), "{scale:?}: {width} vs {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn octave_band_edges_follow_iec_61260() {
+        for scale in [
+            FrequencyScale::Octave,
+            FrequencyScale::ThirdOctave,
+            FrequencyScale::SixthOctave,
+            FrequencyScale::TwelfthOctave,
+        ] {
+            let bands = scale.bands_per_octave().unwrap();
+            let edges = scale.band_edges(0, 20.0, 20000.0);
+            let ratio = OCTAVE_RATIO.powf(1.0 / bands as f32);
+            for pair in edges.windows(2) {
+                assert!(close(pair[1] / pair[0], ratio, 1e-4));
+                let center = (pair[0] * pair[1]).sqrt();
+                assert!((20.0..=20000.0).contains(&center), "{scale:?}: {center}");
+            }
+            // Odd band counts center a band on 1 kHz, even ones put an edge there
+            let at_reference = |freq: f32| close(freq, REFERENCE_FREQ, 1e-4);
+            if bands % 2 == 1 {
+                assert!(
+                    edges
+                        .windows(2)
+                        .any(|pair| at_reference((pair[0] * pair[1]).sqrt()))
+                );
+            } else {
+                assert!(edges.iter().any(|&edge| at_reference(edge)));
+            }
+        }
+        // Nominal 25 Hz to 20 kHz; the 20 Hz band centers just below 20 Hz
+        assert_eq!(
+            FrequencyScale::ThirdOctave
+                .band_edges(0, 20.0, 20000.0)
+                .len(),
+            31
+        );
+        assert_eq!(
+            FrequencyScale::Octave.band_edges(0, 20.0, 20000.0).len(),
+            11
+        );
+    }
+
+    #[test]
+    fn narrow_range_keeps_one_octave_band() {
+        let edges = FrequencyScale::Octave.band_edges(0, 1100.0, 1200.0);
+        assert_eq!(edges.len(), 2);
+        assert!(close((edges[0] * edges[1]).sqrt(), 1000.0, 1e-4));
+    }
+
+    #[test]
+    fn note_edges_sit_between_semitones() {
+        let scale = FrequencyScale::Notes {
+            a4: 440.0,
+            lowest: 57,
+            highest: 81,
+        };
+        let edges = scale.band_edges(0, 20.0, 20000.0);
+        assert_eq!(edges.len(), 26);
+        // A4 is the 13th note
+        assert!(close((edges[12] * edges[13]).sqrt(), 440.0, 1e-4));
+    }
+}
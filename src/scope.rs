@@ -0,0 +1,196 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Samples kept per channel for the oscilloscope.
+pub const MAX_SCOPE_SAMPLES: usize = 1 << 16;
+
+/// Longest trace in samples; the rest of the buffer leaves room to search
+/// for the trigger.
+pub const MAX_SCOPE_SPAN: usize = MAX_SCOPE_SAMPLES / 2;
+
+/// Samples used to estimate the period for `Trigger::Auto`.
+const AUTO_WINDOW: usize = 2048;
+
+/// Where a trace starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Trigger {
+    /// The most recent samples, without alignment.
+    Free,
+    /// Where the signal rises through `level`.
+    Rising { level: f32 },
+    /// Where the signal falls through `level`.
+    Falling { level: f32 },
+    /// At the same phase of the dominant period in every trace: where the
+    /// signal rises through its mean within the last period.
+    #[default]
+    Auto,
+}
+
+impl Trigger {
+    /// One of each kind, with edges at zero.
+    pub fn options() -> [Trigger; 4] {
+        [
+            Trigger::Free,
+            Trigger::Rising { level: 0.0 },
+            Trigger::Falling { level: 0.0 },
+            Trigger::Auto,
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trigger::Free => "Free",
+            Trigger::Rising { .. } => "Rising edge",
+            Trigger::Falling { .. } => "Falling edge",
+            Trigger::Auto => "Auto",
+        }
+    }
+
+    /// True if both are the same trigger, ignoring parameters.
+    pub fn same_kind(&self, other: &Trigger) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Start of the latest trace of `span` samples in `samples` that begins
+    /// at the trigger, or `None` if the trigger does not occur.
+    pub fn find(&self, samples: &[f32], span: usize, sample_rate: u32) -> Option<usize> {
+        let end = samples.len().checked_sub(span)?;
+        match *self {
+            Trigger::Free => Some(end),
+            Trigger::Rising { level } => {
+                last_crossing(&samples[..=end], |a, b| a < level && b >= level)
+            }
+            Trigger::Falling { level } => {
+                last_crossing(&samples[..=end], |a, b| a > level && b <= level)
+            }
+            Trigger::Auto => {
+                let period = dominant_period(samples, sample_rate)?;
+                let start = end.saturating_sub(period);
+                let cycle = &samples[start..=end];
+                let mean = cycle.iter().sum::<f32>() / cycle.len() as f32;
+                let at = last_crossing(cycle, |a, b| a < mean && b >= mean).or_else(|| {
+                    // No crossing within a period; align to its peak instead
+                    (0..cycle.len()).max_by(|&a, &b| cycle[a].total_cmp(&cycle[b]))
+                })?;
+                Some(start + at)
+            }
+        }
+    }
+}
+
+/// Index of the last sample where `crosses(previous, sample)` holds.
+fn last_crossing(samples: &[f32], crosses: impl Fn(f32, f32) -> bool) -> Option<usize> {
+    (1..samples.len())
+        .rev()
+        .find(|&i| crosses(samples[i - 1], samples[i]))
+}
+
+/// Period in samples of the strongest repetition between 20 Hz and 5 kHz in
+/// the latest samples, from the first autocorrelation peak after it turns
+/// negative.
+fn dominant_period(samples: &[f32], sample_rate: u32) -> Option<usize> {
+    let x = &samples[samples.len().saturating_sub(AUTO_WINDOW)..];
+    let n = x.len();
+    let mean = x.iter().sum::<f32>() / n.max(1) as f32;
+    let x: Vec<f32> = x.iter().map(|s| s - mean).collect();
+    let correlation = |lag: usize| {
+        x[..n - lag]
+            .iter()
+            .zip(&x[lag..])
+            .map(|(a, b)| a * b)
+            .sum::<f32>()
+            / (n - lag) as f32
+    };
+
+    let energy = correlation(0);
+    if energy < 1e-10 {
+        return None;
+    }
+    let min_lag = (sample_rate as usize / 5000).max(2);
+    let max_lag = (sample_rate as usize / 20).min(n / 2);
+    let mut dipped = false;
+    let mut previous: Option<(usize, f32)> = None;
+    for lag in min_lag..=max_lag {
+        let r = correlation(lag);
+        if !dipped {
+            dipped = r < 0.0;
+            continue;
+        }
+        // The first maximum once the correlation is back above zero
+        if let Some((peak, max)) = previous
+            && max > 0.0
+            && r < max
+        {
+            return (max > 0.3 * energy).then_some(peak);
+        }
+        previous = Some((lag, r));
+    }
+    None
+}
+
+/// Samples of one trace, starting at the trigger.
+pub struct ScopeTrace {
+    /// The analyzed channel, or the left one in stereo mode.
+    pub left: Vec<f32>,
+    pub right: Option<Vec<f32>>,
+    /// False if the trigger did not occur and the trace shows the most
+    /// recent samples instead.
+    pub triggered: bool,
+    pub sample_rate: u32,
+}
+
+/// Ring buffer of the most recent samples for the oscilloscope.
+#[derive(Default)]
+pub struct Scope {
+    /// The second channel is only filled with stereo input.
+    channels: [VecDeque<f32>; 2],
+}
+
+impl Scope {
+    pub fn push(&mut self, left: &[f32], right: Option<&[f32]>) {
+        // Start over when switching between mono and stereo
+        if right.is_some() == self.channels[1].is_empty() {
+            self.clear();
+        }
+        Self::append(&mut self.channels[0], left);
+        if let Some(right) = right {
+            Self::append(&mut self.channels[1], right);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.channels.iter_mut().for_each(VecDeque::clear);
+    }
+
+    fn append(channel: &mut VecDeque<f32>, samples: &[f32]) {
+        let samples = &samples[samples.len().saturating_sub(MAX_SCOPE_SAMPLES)..];
+        channel.extend(samples);
+        if channel.len() > MAX_SCOPE_SAMPLES {
+            channel.drain(..channel.len() - MAX_SCOPE_SAMPLES);
+        }
+    }
+
+    /// The latest `span` samples that start at `trigger`, found on the left
+    /// channel. With fewer samples, the trace is shorter.
+    pub fn trace(&self, trigger: Trigger, span: usize, sample_rate: u32) -> ScopeTrace {
+        let left = self.channels[0].iter().copied().collect::<Vec<_>>();
+        let span = span.clamp(1, MAX_SCOPE_SPAN).min(left.len());
+        let found = match span {
+            0 => None,
+            span => trigger.find(&left, span, sample_rate),
+        };
+        let start = found.unwrap_or(left.len() - span);
+        let range = start..start + span;
+        let right = &self.channels[1];
+        let right = (!right.is_empty() && right.len() == left.len())
+            .then(|| right.range(range.clone()).copied().collect());
+        ScopeTrace {
+            left: left[range].to_vec(),
+            right,
+            triggered: found.is_some(),
+            sample_rate,
+        }
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+/// Largest number of frames the spectrogram keeps.
+pub const MAX_SPECTROGRAM_DEPTH: usize = 4096;
+
+/// Level range of the spectrogram in relative output mode, which has no
+/// absolute level of its own.
+pub const SPECTROGRAM_RANGE_DB: (f32, f32) = (-90.0, 0.0);
+
+/// Ring buffer of the most recent spectrum frames.
+pub struct Spectrogram {
+    frames: VecDeque<Vec<f32>>,
+    depth: usize,
+    /// Frames pushed since the history was last cleared.
+    pushed: u64,
+    /// Counts the times the history was cleared.
+    generation: u64,
+}
+
+/// Position of a reader in the frames of a spectrogram, to fetch only the
+/// frames added since its last read.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpectrogramCursor {
+    generation: u64,
+    pushed: u64,
+    depth: usize,
+    /// dB range and full-scale power the levels were mapped with.
+    pub(crate) mapping: (f32, f32, f32),
+}
+
+/// Spectrogram levels read from a cursor.
+pub struct SpectrogramUpdate {
+    /// True if `frames` is the whole history and replaces what was read
+    /// before.
+    pub reset: bool,
+    /// Levels from 0 to 1, oldest frame first.
+    pub frames: Vec<Vec<f32>>,
+    /// Number of frames the history keeps.
+    pub depth: usize,
+    /// Where the next read continues.
+    pub cursor: SpectrogramCursor,
+}
+
+impl Spectrogram {
+    pub fn new(depth: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(depth),
+            depth,
+            pushed: 0,
+            generation: 0,
+        }
+    }
+
+    /// Number of frames kept.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Keep at most `depth` frames, dropping the oldest.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.truncate();
+    }
+
+    /// Add the newest frame. Frames of a different length than the stored
+    /// ones replace the history, since they belong to another layout.
+    pub fn push(&mut self, frame: Vec<f32>) {
+        if self.frames.front().is_some_and(|f| f.len() != frame.len()) {
+            self.clear();
+        }
+        self.frames.push_back(frame);
+        self.pushed += 1;
+        self.truncate();
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.pushed = 0;
+        self.generation += 1;
+    }
+
+    /// Cursor past the newest frame, for levels mapped with `mapping`.
+    pub(crate) fn cursor(&self, mapping: (f32, f32, f32)) -> SpectrogramCursor {
+        SpectrogramCursor {
+            generation: self.generation,
+            pushed: self.pushed,
+            depth: self.depth,
+            mapping,
+        }
+    }
+
+    /// Frames pushed after `cursor`, oldest first, or `None` if the history
+    /// was cleared or resized since.
+    pub fn frames_since(
+        &self,
+        cursor: SpectrogramCursor,
+    ) -> Option<impl ExactSizeIterator<Item = &Vec<f32>>> {
+        if cursor.generation != self.generation || cursor.depth != self.depth {
+            return None;
+        }
+        let new = (self.pushed - cursor.pushed).min(self.frames.len() as u64) as usize;
+        Some(self.frames.range(self.frames.len() - new..))
+    }
+
+    /// Stored frames, oldest first.
+    pub fn frames(&self) -> impl ExactSizeIterator<Item = &Vec<f32>> {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn truncate(&mut self) {
+        while self.frames.len() > self.depth {
+            self.frames.pop_front();
+        }
+    }
+}
+
+/// Maps spectrogram levels from 0 to 1 to colors.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Colormap {
+    #[default]
+    Viridis,
+    Magma,
+    Inferno,
+    Grayscale,
+}
+
+impl Colormap {
+    pub const ALL: [Colormap; 4] = [
+        Colormap::Viridis,
+        Colormap::Magma,
+        Colormap::Inferno,
+        Colormap::Grayscale,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "Viridis",
+            Colormap::Magma => "Magma",
+            Colormap::Inferno => "Inferno",
+            Colormap::Grayscale => "Grayscale",
+        }
+    }
+
+    /// Evenly spaced sRGB stops from low to high level, approximating the
+    /// matplotlib maps.
+    fn stops(&self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Viridis => &[
+                [0x44, 0x01, 0x54],
+                [0x48, 0x28, 0x78],
+                [0x3e, 0x4a, 0x89],
+                [0x31, 0x68, 0x8e],
+                [0x26, 0x82, 0x8e],
+                [0x1f, 0x9e, 0x89],
+                [0x35, 0xb7, 0x79],
+                [0x6d, 0xcd, 0x59],
+                [0xb4, 0xde, 0x2c],
+                [0xfd, 0xe7, 0x25],
+            ],
+            Colormap::Magma => &[
+                [0x00, 0x00, 0x04],
+                [0x18, 0x0f, 0x3d],
+                [0x44, 0x0f, 0x76],
+                [0x72, 0x1f, 0x81],
+                [0x9e, 0x2f, 0x7f],
+                [0xcd, 0x40, 0x71],
+                [0xf1, 0x60, 0x5d],
+                [0xfd, 0x96, 0x68],
+                [0xfe, 0xca, 0x8d],
+                [0xfc, 0xfd, 0xbf],
+            ],
+            Colormap::Inferno => &[
+                [0x00, 0x00, 0x04],
+                [0x1b, 0x0c, 0x41],
+                [0x4a, 0x0c, 0x6b],
+                [0x78, 0x1c, 0x6d],
+                [0xa5, 0x2c, 0x60],
+                [0xcf, 0x44, 0x46],
+                [0xed, 0x69, 0x25],
+                [0xfb, 0x9b, 0x06],
+                [0xf7, 0xd1, 0x3d],
+                [0xfc, 0xff, 0xa4],
+            ],
+            Colormap::Grayscale => &[[0, 0, 0], [255, 255, 255]],
+        }
+    }
+
+    /// sRGB color of `level`, clamped to 0..=1.
+    pub fn color(&self, level: f32) -> [u8; 3] {
+        let stops = self.stops();
+        let x = level.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+        let i = (x as usize).min(stops.len() - 2);
+        let t = x - i as f32;
+        let (a, b) = (stops[i], stops[i + 1]);
+        std::array::from_fn(|c| (a[c] as f32 + t * (b[c] as f32 - a[c] as f32)).round() as u8)
+    }
+}
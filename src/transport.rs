@@ -0,0 +1,226 @@
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaybackState {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+/// What happens when playback reaches the end of the track.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndBehavior {
+    Stop,
+    Loop,
+}
+
+struct TransportState {
+    state: PlaybackState,
+    position: u64,
+    duration: Option<u64>,
+    loop_region: Option<(u64, u64)>,
+    end_behavior: EndBehavior,
+    pending_seek: Option<u64>,
+    error: Option<String>,
+}
+
+/// Playback transport shared between the audio callback and the frontends.
+/// Positions are counted in frames at `sample_rate`. Frontends request changes
+/// (play, pause, seek, looping) and the audio callback applies them.
+/// Audio errors are reported here as well so frontends can show them.
+pub struct Transport {
+    sample_rate: AtomicU32,
+    inner: Mutex<TransportState>,
+}
+
+impl Transport {
+    /// Create a transport for a track of `duration` frames (if known), initially playing.
+    pub fn new(sample_rate: u32, duration: Option<u64>) -> Self {
+        Self {
+            sample_rate: AtomicU32::new(sample_rate.max(1)),
+            inner: Mutex::new(TransportState {
+                state: PlaybackState::Playing,
+                position: 0,
+                duration,
+                loop_region: None,
+                end_behavior: EndBehavior::Stop,
+                pending_seek: None,
+                error: None,
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TransportState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn to_frames(&self, seconds: f64) -> u64 {
+        (seconds.max(0.0) * self.sample_rate() as f64) as u64
+    }
+
+    fn to_seconds(&self, frames: u64) -> f64 {
+        frames as f64 / self.sample_rate() as f64
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate.load(Ordering::Relaxed)
+    }
+
+    /// Switch to a reopened source running at `sample_rate` with `duration`
+    /// frames. The play state, position and loop region are kept; a seek to
+    /// the current position is requested so a seekable source resumes there.
+    pub fn reset(&self, sample_rate: u32, duration: Option<u64>) {
+        let sample_rate = sample_rate.max(1);
+        let mut inner = self.lock();
+        let ratio = sample_rate as f64 / self.sample_rate() as f64;
+        let rescale = |frames: u64| (frames as f64 * ratio) as u64;
+
+        inner.position = rescale(inner.position);
+        inner.loop_region = inner
+            .loop_region
+            .map(|(start, end)| (rescale(start), rescale(end)));
+        inner.duration = duration;
+        inner.pending_seek = (duration.is_some() && inner.position > 0).then_some(inner.position);
+        self.sample_rate.store(sample_rate, Ordering::Relaxed);
+    }
+
+    pub fn state(&self) -> PlaybackState {
+        self.lock().state
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state() == PlaybackState::Playing
+    }
+
+    pub fn play(&self) {
+        self.lock().state = PlaybackState::Playing;
+    }
+
+    pub fn pause(&self) {
+        let mut inner = self.lock();
+        if inner.state == PlaybackState::Playing {
+            inner.state = PlaybackState::Paused;
+        }
+    }
+
+    pub fn toggle_play(&self) {
+        if self.is_playing() {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Stop playback and rewind to the start of the track.
+    pub fn stop(&self) {
+        let mut inner = self.lock();
+        inner.state = PlaybackState::Stopped;
+        inner.position = 0;
+        inner.pending_seek = Some(0);
+    }
+
+    /// Request a seek to `seconds`, clamped to the track duration.
+    pub fn seek(&self, seconds: f64) {
+        let mut frames = self.to_frames(seconds);
+        let mut inner = self.lock();
+        if let Some(duration) = inner.duration {
+            frames = frames.min(duration);
+        }
+        inner.position = frames;
+        inner.pending_seek = Some(frames);
+    }
+
+    pub fn position_seconds(&self) -> f64 {
+        self.to_seconds(self.lock().position)
+    }
+
+    pub fn duration_seconds(&self) -> Option<f64> {
+        self.lock().duration.map(|d| self.to_seconds(d))
+    }
+
+    /// Loop playback between `start` and `end` seconds, or disable the loop region with `None`.
+    pub fn set_loop_region(&self, region: Option<(f64, f64)>) {
+        let region = region
+            .map(|(start, end)| (self.to_frames(start), self.to_frames(end)))
+            .filter(|(start, end)| end > start);
+        self.lock().loop_region = region;
+    }
+
+    pub fn loop_region(&self) -> Option<(f64, f64)> {
+        self.lock()
+            .loop_region
+            .map(|(start, end)| (self.to_seconds(start), self.to_seconds(end)))
+    }
+
+    pub fn set_end_behavior(&self, behavior: EndBehavior) {
+        self.lock().end_behavior = behavior;
+    }
+
+    pub fn end_behavior(&self) -> EndBehavior {
+        self.lock().end_behavior
+    }
+
+    /// Record an audio error for the frontends to display.
+    pub fn report_error(&self, error: impl Display) {
+        let message = error.to_string();
+        eprintln!("{}", message);
+        self.lock().error = Some(message);
+    }
+
+    /// The most recent unacknowledged error, if any.
+    pub fn error(&self) -> Option<String> {
+        self.lock().error.clone()
+    }
+
+    pub fn clear_error(&self) {
+        self.lock().error = None;
+    }
+
+    /// Take a pending seek target (in frames) requested by a frontend.
+    /// Called from the audio callback.
+    pub fn take_seek(&self) -> Option<u64> {
+        self.lock().pending_seek.take()
+    }
+
+    /// Frames left until the end of the loop region, if one is set.
+    /// Called from the audio callback.
+    pub fn frames_to_loop_end(&self) -> Option<u64> {
+        let inner = self.lock();
+        inner
+            .loop_region
+            .map(|(_, end)| end.saturating_sub(inner.position))
+    }
+
+    /// Advance the play position by `frames`. Returns a seek target (in frames)
+    /// when the position has passed the end of the loop region.
+    /// Called from the audio callback.
+    pub fn advance(&self, frames: u64) -> Option<u64> {
+        let mut inner = self.lock();
+        inner.position += frames;
+        match inner.loop_region {
+            Some((start, end)) if inner.position >= end => {
+                inner.position = start;
+                Some(start)
+            }
+            _ => None,
+        }
+    }
+
+    /// Handle the end of the track. Returns the seek target to rewind to;
+    /// playback continues when looping and stops otherwise.
+    /// Called from the audio callback.
+    pub fn end_of_track(&self) -> u64 {
+        let mut inner = self.lock();
+        let start = inner.loop_region.map_or(0, |(start, _)| start);
+        match inner.end_behavior {
+            EndBehavior::Loop => inner.position = start,
+            EndBehavior::Stop => {
+                inner.state = PlaybackState::Stopped;
+                inner.position = 0;
+            }
+        }
+        inner.position
+    }
+}
@@ -1,65 +1,431 @@
-use spectrum_analyzer::scaling::divide_by_N_sqrt;
-use spectrum_analyzer::windows::hann_window;
-use spectrum_analyzer::{FrequencyLimit, FrequencySpectrum, samples_fft_to_spectrum};
+use std::collections::VecDeque;
+use std::sync::Mutex;
 
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{AnalysisMethod, SpectrumBackend};
+use crate::binning::{BinAggregation, BinInterpolation};
+use crate::channels::ChannelSource;
+use crate::error::ConfigError;
 use crate::filters::{AttackReleaseFilter, BinLayout, FilterManager, GaussianFilter};
+use crate::normalizers::{FrameLevels, Normalizer, NormalizerPreset, RmsLoudnessNormalizer};
+use crate::scale::FrequencyScale;
+use crate::scope::{Scope, ScopeTrace, Trigger};
+use crate::spectrogram::{
+    MAX_SPECTROGRAM_DEPTH, SPECTROGRAM_RANGE_DB, Spectrogram, SpectrogramCursor, SpectrogramUpdate,
+};
+use crate::window::WindowFunction;
+
+/// Largest fraction of a window that consecutive windows may share.
+pub const MAX_OVERLAP: f32 = 0.95;
+
+/// Shortest supported analysis window in samples.
+pub const MIN_WINDOW_SIZE: usize = 16;
+
+/// Largest FFT the backend can compute, including zero padding.
+pub const MAX_FFT_SIZE: usize = 32768;
+
+/// Largest supported number of displayed bins.
+pub const MAX_BINS: usize = 1024;
+
+const DEFAULT_SPECTROGRAM_DEPTH: usize = 256;
+
+/// How bin powers are mapped to bar heights from 0 to 1.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum OutputMode {
+    /// Bin magnitudes scaled by the configured `Normalizer`.
+    #[default]
+    Relative,
+    /// Level in dBFS, where a full-scale sine reads 0 dB; `floor_db` maps to
+    /// an empty bar and `ceiling_db` to a full one. With FFT analysis, sum
+    /// and max aggregation read the level of a tone, while mean and RMS
+    /// spread it over the bin.
+    Decibels { floor_db: f32, ceiling_db: f32 },
+}
+
+impl OutputMode {
+    /// One of each kind, with a -90 to 0 dB range.
+    pub fn options() -> [OutputMode; 2] {
+        [
+            OutputMode::Relative,
+            OutputMode::Decibels {
+                floor_db: -90.0,
+                ceiling_db: 0.0,
+            },
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            OutputMode::Relative => "Relative",
+            OutputMode::Decibels { .. } => "dBFS",
+        }
+    }
+
+    /// True if both are the same mode, ignoring parameters.
+    pub fn same_kind(&self, other: &OutputMode) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
 
 pub struct VisualizerConfig {
     pub sample_rate: u32,
-    pub window_size: usize,
-    pub num_bins: usize,
+    window_size: usize,
+    /// The FFT is `zero_padding` times the window size rounded up to a power
+    /// of two; the extra samples are zeros.
+    zero_padding: usize,
+    window_function: WindowFunction,
+    /// Fraction of each window shared with the previous one.
+    overlap: f32,
+    /// Coefficients of `window_function` for `window_size`, gain compensated.
+    window_coefficients: Vec<f32>,
+    /// Requested number of bins; fractional-octave scales derive theirs from
+    /// the frequency range, see `layout.num_bins()`.
+    num_bins: usize,
     base_min_freq: f32,
     min_freq: f32,
     max_freq: f32,
     pub filter_manager: FilterManager,
     pub layout: BinLayout,
+    scale: FrequencyScale,
+    /// Channel count of the incoming audio.
+    pub channels: usize,
+    /// Part of the incoming audio that is analyzed.
+    pub channel_source: ChannelSource,
+    /// Analyze left and right channels separately when the input is stereo.
+    pub stereo: bool,
     window_rms: f32,
-    rms_reference: f32,
-    rms_floor: f32,
-    rms_gamma: f32,
+    /// Locked only while a frame is normalized, so the config can be shared
+    /// like the filters.
+    normalizer: Mutex<Box<dyn Normalizer>>,
+    pub output: OutputMode,
+    /// Fills bins that no FFT line falls into.
+    pub interpolation: BinInterpolation,
+    /// Combines the FFT lines within a bin.
+    pub aggregation: BinAggregation,
+    analysis: AnalysisMethod,
+    /// Frames kept for the spectrogram.
+    spectrogram_depth: usize,
 }
 
 impl VisualizerConfig {
-    pub fn set_num_bins(&mut self, num_bins: usize) {
-        self.num_bins = num_bins.max(1);
-        self.layout = BinLayout::build_layout(self.num_bins, self.min_freq, self.max_freq, true);
-        self.filter_manager.refresh_layout();
+    /// Requested number of bins; see `layout.num_bins()` for the actual one.
+    pub fn num_bins(&self) -> usize {
+        self.num_bins
     }
 
-    pub fn set_window_size(&mut self, window_size: usize) {
-        if window_size == self.window_size {
-            return;
+    pub fn set_num_bins(&mut self, num_bins: usize) -> Result<(), ConfigError> {
+        if !(1..=MAX_BINS).contains(&num_bins) {
+            return Err(ConfigError::NumBins(num_bins));
         }
-        self.window_size = window_size.max(1);
+        self.num_bins = num_bins;
+        self.rebuild_layout();
+        Ok(())
+    }
 
-        // reset temporal filters (length changes)
-        self.filter_manager.reset_temporal_filters();
+    /// Analysis window length in samples.
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Set the analysis window length. Any length from `MIN_WINDOW_SIZE` up
+    /// is accepted as long as the padded FFT fits in `MAX_FFT_SIZE`.
+    pub fn set_window_size(&mut self, window_size: usize) -> Result<(), ConfigError> {
+        self.set_window(window_size, self.zero_padding)
+    }
+
+    pub fn zero_padding(&self) -> usize {
+        self.zero_padding
+    }
+
+    /// Set the zero-padding factor, a power of two. Padding interpolates the
+    /// spectrum between FFT bins, which helps short windows.
+    pub fn set_zero_padding(&mut self, factor: usize) -> Result<(), ConfigError> {
+        self.set_window(self.window_size, factor)
+    }
+
+    /// Samples each spectrum frame analyzes: the window, or the longer bass
+    /// window of multi-resolution analysis.
+    pub fn history_size(&self) -> usize {
+        match self.analysis {
+            AnalysisMethod::MultiResolution { bass_window, .. } => {
+                self.window_size.max(bass_window)
+            }
+            _ => self.window_size,
+        }
+    }
+
+    /// Number of FFT points: the window size rounded up to a power of two,
+    /// times the zero-padding factor.
+    pub fn fft_size(&self) -> usize {
+        fft_size(self.window_size, self.zero_padding)
+    }
+
+    /// Set window size and zero-padding factor together, so that one can grow
+    /// while the other shrinks.
+    pub fn set_window(
+        &mut self,
+        window_size: usize,
+        zero_padding: usize,
+    ) -> Result<(), ConfigError> {
+        if !zero_padding.is_power_of_two() {
+            return Err(ConfigError::ZeroPadding(zero_padding));
+        }
+        if window_size < MIN_WINDOW_SIZE || fft_size(window_size, zero_padding) > MAX_FFT_SIZE {
+            return Err(ConfigError::WindowSize {
+                window_size,
+                zero_padding,
+            });
+        }
+        // A new window length or FFT line spacing changes the levels, so the
+        // filtered history no longer compares
+        let rescaled = window_size != self.window_size
+            || fft_size(window_size, zero_padding) != self.fft_size();
+        self.zero_padding = zero_padding;
+        if rescaled {
+            self.filter_manager.reset_temporal_filters();
+            self.normalizer_mut().reset();
+        }
+        if window_size == self.window_size {
+            return Ok(());
+        }
+        self.window_size = window_size;
+        self.window_coefficients = self
+            .window_function
+            .compensated_coefficients(self.window_size);
 
         // Update min_freq adaptively
-        let resolution = self.sample_rate as f32 / self.window_size as f32;
-        // Factor 2.0 → require ~2 FFT bins before first visual bin.
-        let dyn_min = resolution * 2.0;
-        let new_min = self.base_min_freq.max(dyn_min);
+        let new_min = self.adaptive_min_freq();
         if (new_min - self.min_freq).abs() > 0.1 {
             self.min_freq = new_min;
-            self.layout =
-                BinLayout::build_layout(self.num_bins, self.min_freq, self.max_freq, true);
-            self.filter_manager.refresh_layout();
+            self.rebuild_layout();
+        }
+        Ok(())
+    }
+
+    /// Fraction of each window shared with the previous one.
+    pub fn overlap(&self) -> f32 {
+        self.overlap
+    }
+
+    /// Set the overlap of consecutive windows, from 0 up to `MAX_OVERLAP`.
+    pub fn set_overlap(&mut self, overlap: f32) -> Result<(), ConfigError> {
+        if !(0.0..=MAX_OVERLAP).contains(&overlap) {
+            return Err(ConfigError::Overlap(overlap));
+        }
+        self.overlap = overlap;
+        Ok(())
+    }
+
+    /// Samples between the starts of consecutive spectrum frames.
+    pub fn hop_size(&self) -> usize {
+        let hop = (self.window_size as f32 * (1.0 - self.overlap)).round() as usize;
+        hop.clamp(1, self.window_size)
+    }
+
+    /// Set the overlap so that frames start `hop_size` samples apart. The hop
+    /// scales with the window size from then on.
+    pub fn set_hop_size(&mut self, hop_size: usize) -> Result<(), ConfigError> {
+        if !(1..=self.window_size).contains(&hop_size) {
+            return Err(ConfigError::HopSize(hop_size));
         }
+        let overlap = 1.0 - hop_size as f32 / self.window_size as f32;
+        self.set_overlap(overlap)
+            .map_err(|_| ConfigError::HopSize(hop_size))
+    }
+
+    pub fn window_function(&self) -> WindowFunction {
+        self.window_function
+    }
+
+    pub fn set_window_function(&mut self, window_function: WindowFunction) {
+        if window_function != self.window_function {
+            self.window_function = window_function;
+            self.window_coefficients = window_function.compensated_coefficients(self.window_size);
+        }
+    }
+
+    pub fn scale(&self) -> FrequencyScale {
+        self.scale
+    }
+
+    pub fn set_scale(&mut self, scale: FrequencyScale) {
+        if scale != self.scale {
+            self.scale = scale;
+            self.rebuild_layout();
+        }
+    }
+
+    /// Switch to audio at `sample_rate`. An upper limit at Nyquist follows the new rate.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        let sample_rate = sample_rate.max(1);
+        if sample_rate == self.sample_rate {
+            return;
+        }
+        let at_nyquist = self.max_freq >= self.sample_rate as f32 / 2.0 - 1.0;
+        self.sample_rate = sample_rate;
+        let max_freq = if at_nyquist { f32::MAX } else { self.max_freq };
+        self.apply_frequency_range(self.base_min_freq, max_freq);
+        self.filter_manager.reset_temporal_filters();
+        self.normalizer_mut().reset();
+    }
+
+    /// Limit the displayed range to `min_freq`..`max_freq` Hz.
+    /// The upper limit is clamped to Nyquist; the lower limit is raised when
+    /// the window is too short to resolve it.
+    pub fn set_frequency_range(&mut self, min_freq: f32, max_freq: f32) -> Result<(), ConfigError> {
+        if !(min_freq.is_finite() && min_freq > 0.0 && max_freq > min_freq) {
+            return Err(ConfigError::FrequencyRange(min_freq, max_freq));
+        }
+        self.apply_frequency_range(min_freq, max_freq);
+        Ok(())
+    }
+
+    fn apply_frequency_range(&mut self, min_freq: f32, max_freq: f32) {
+        let nyquist = self.sample_rate as f32 / 2.0;
+        self.max_freq = max_freq.clamp(2.0, nyquist);
+        self.base_min_freq = min_freq.clamp(1.0, self.max_freq - 1.0);
+        self.min_freq = self.adaptive_min_freq();
+        self.rebuild_layout();
+    }
+
+    /// Requested (lower, upper) frequency limits in Hz.
+    pub fn frequency_range(&self) -> (f32, f32) {
+        (self.base_min_freq, self.max_freq)
+    }
+
+    pub fn analysis(&self) -> AnalysisMethod {
+        self.analysis
+    }
+
+    /// Select the analysis method. Constant-Q sets the number of bins of
+    /// continuous scales from its bins per octave.
+    pub fn set_analysis(&mut self, analysis: AnalysisMethod) -> Result<(), ConfigError> {
+        analysis.validate()?;
+        if analysis != self.analysis {
+            self.analysis = analysis;
+            // A longer bass window resolves lower frequencies
+            self.min_freq = self.adaptive_min_freq();
+            self.rebuild_layout();
+        }
+        Ok(())
+    }
+
+    /// Replace the normalizer used in relative output mode.
+    pub fn set_normalizer(&mut self, normalizer: impl Into<Box<dyn Normalizer>>) {
+        self.normalizer = Mutex::new(normalizer.into());
+    }
+
+    pub fn normalizer_mut(&mut self) -> &mut dyn Normalizer {
+        let normalizer = self
+            .normalizer
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        normalizer.as_mut()
+    }
+
+    /// Parameters of the current normalizer.
+    pub fn normalizer_preset(&self) -> NormalizerPreset {
+        match self.normalizer.lock() {
+            Ok(normalizer) => normalizer.preset(),
+            Err(poisoned) => poisoned.into_inner().preset(),
+        }
+    }
+
+    /// Number of frames kept for the spectrogram.
+    pub fn spectrogram_depth(&self) -> usize {
+        self.spectrogram_depth
+    }
+
+    pub fn set_spectrogram_depth(&mut self, depth: usize) -> Result<(), ConfigError> {
+        if !(1..=MAX_SPECTROGRAM_DEPTH).contains(&depth) {
+            return Err(ConfigError::SpectrogramDepth(depth));
+        }
+        self.spectrogram_depth = depth;
+        Ok(())
+    }
+
+    /// Time covered by the spectrogram in seconds at the current hop size.
+    pub fn spectrogram_span(&self) -> f32 {
+        (self.spectrogram_depth * self.hop_size()) as f32 / self.sample_rate as f32
+    }
+
+    /// Keep as many frames as cover `seconds` at the current hop size.
+    pub fn set_spectrogram_span(&mut self, seconds: f32) -> Result<(), ConfigError> {
+        let depth = (seconds * self.sample_rate as f32 / self.hop_size() as f32).round();
+        self.set_spectrogram_depth(depth.max(0.0) as usize)
+    }
+
+    /// Window coefficients for `window_size`, gain compensated.
+    pub(crate) fn window_coefficients(&self) -> &[f32] {
+        &self.window_coefficients
+    }
+
+    fn rebuild_layout(&mut self) {
+        let num_bins = match self.analysis {
+            AnalysisMethod::ConstantQ {
+                bins_per_octave, ..
+            } => {
+                let octaves = (self.max_freq / self.min_freq).log2();
+                ((octaves * bins_per_octave as f32).ceil() as usize).clamp(1, MAX_BINS)
+            }
+            AnalysisMethod::Fft | AnalysisMethod::MultiResolution { .. } => self.num_bins,
+        };
+        self.layout = BinLayout::build_layout(num_bins, self.min_freq, self.max_freq, self.scale);
+        self.filter_manager.update_layout(self.layout.clone());
+    }
+
+    fn adaptive_min_freq(&self) -> f32 {
+        let resolution = self.sample_rate as f32 / self.history_size() as f32;
+        // Factor 2.0 → require ~2 FFT bins before first visual bin.
+        let dyn_min = resolution * 2.0;
+        self.base_min_freq.max(dyn_min).min(self.max_freq - 1.0)
     }
 }
 
+/// Level of `power` in dB relative to `full_scale`, mapped from
+/// `floor_db`..`ceiling_db` to 0..=1.
+fn db_level(power: f32, full_scale: f32, floor_db: f32, ceiling_db: f32) -> f32 {
+    let db = 10.0 * (power / full_scale).max(1e-20).log10();
+    ((db - floor_db) / (ceiling_db - floor_db)).clamp(0.0, 1.0)
+}
+
+fn fft_size(window_size: usize, zero_padding: usize) -> usize {
+    window_size.next_power_of_two().saturating_mul(zero_padding)
+}
+
 pub struct Visualizer {
-    spectrum: Option<FrequencySpectrum>,
+    /// Power per bin of the last frame.
+    bins: Option<Vec<f32>>,
+    /// Right channel bins in stereo mode; `bins` then holds the left channel.
+    bins_right: Option<Vec<f32>>,
+    backend: Box<dyn SpectrumBackend>,
+    /// Most recent samples of the analyzed channels, at most one window long.
+    /// The second channel is only filled in stereo mode.
+    history: [VecDeque<f32>; 2],
+    /// Samples pushed since the last spectrum frame.
+    since_hop: usize,
+    /// Spatially filtered bin powers of the recent frames; stereo frames are
+    /// averaged over both channels.
+    spectrogram: Spectrogram,
+    /// Recent pushed samples for the oscilloscope.
+    scope: Scope,
     pub config: VisualizerConfig,
 }
 
 impl Visualizer {
     /// Default constructor. Frequency limits set to 20 Hz - Nyquist.
+    /// `window_size` is clamped to `MIN_WINDOW_SIZE..=MAX_FFT_SIZE` and
+    /// `num_bins` to `1..=MAX_BINS`.
     pub fn new(sample_rate: u32, window_size: usize, num_bins: usize) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let window_size = window_size.clamp(MIN_WINDOW_SIZE, MAX_FFT_SIZE);
+        let num_bins = num_bins.clamp(1, MAX_BINS);
         let min_freq = 20.0;
         let max_freq = sample_rate as f32 / 2.0;
-        let layout = BinLayout::build_layout(num_bins, min_freq, max_freq, true);
+        let layout =
+            BinLayout::build_layout(num_bins, min_freq, max_freq, FrequencyScale::default());
 
         let mut filter_manager = FilterManager::new(layout.clone());
         filter_manager.add_spatial_filter(GaussianFilter::new(3.0, 2, 3));
@@ -68,101 +434,581 @@ impl Visualizer {
         let config = VisualizerConfig {
             sample_rate,
             window_size,
+            zero_padding: 1,
+            window_function: WindowFunction::default(),
+            overlap: 0.75,
+            window_coefficients: WindowFunction::default().compensated_coefficients(window_size),
             num_bins,
             base_min_freq: min_freq,
             min_freq,
             max_freq,
             filter_manager,
             layout,
+            scale: FrequencyScale::default(),
+            channels: 1,
+            channel_source: ChannelSource::default(),
+            stereo: false,
             window_rms: 0.0,
-            rms_reference: 0.6,
-            rms_floor: 0.01,
-            rms_gamma: 0.7,
+            normalizer: Mutex::new(RmsLoudnessNormalizer::default().into()),
+            output: OutputMode::default(),
+            interpolation: BinInterpolation::default(),
+            aggregation: BinAggregation::default(),
+            analysis: AnalysisMethod::default(),
+            spectrogram_depth: DEFAULT_SPECTROGRAM_DEPTH,
         };
         let visualizer = Self {
-            spectrum: None,
+            bins: None,
+            bins_right: None,
+            backend: AnalysisMethod::default().backend(),
+            history: Default::default(),
+            since_hop: 0,
+            spectrogram: Spectrogram::new(DEFAULT_SPECTROGRAM_DEPTH),
+            scope: Scope::default(),
             config,
         };
         visualizer.config.filter_manager.refresh_layout();
         visualizer
     }
 
-    pub fn update_spectrum(&mut self, samples: &[f32]) {
-        let window = hann_window(&samples[0..self.config.window_size]);
-        let mut rms = 0.0_f32;
-        for &x in samples.iter().take(self.config.window_size) {
-            rms += x * x;
-        }
-        rms /= self.config.window_size as f32;
-        rms = rms.sqrt();
+    /// Compute the spectrum of the first `history_size()` samples.
+    pub fn update_spectrum(&mut self, samples: &[f32]) -> Result<(), ConfigError> {
+        self.check_length(samples)?;
+        self.sync_backend();
+        let (bins, rms) = Self::compute_spectrum(self.backend.as_mut(), &self.config, samples);
         self.config.window_rms = rms;
+        self.bins = Some(bins);
+        self.bins_right = None;
+        self.record_frame();
+        Ok(())
+    }
 
-        let spectrum = samples_fft_to_spectrum(
-            &window,
-            self.config.sample_rate,
-            FrequencyLimit::Range(self.config.min_freq, self.config.max_freq),
-            Some(&divide_by_N_sqrt),
-        )
-        .ok();
-
-        self.spectrum = spectrum;
+    /// Compute separate spectra for the left and right channel.
+    pub fn update_stereo_spectrum(
+        &mut self,
+        left: &[f32],
+        right: &[f32],
+    ) -> Result<(), ConfigError> {
+        self.check_length(left)?;
+        self.check_length(right)?;
+        self.sync_backend();
+        let backend = self.backend.as_mut();
+        let (bins_left, rms_left) = Self::compute_spectrum(backend, &self.config, left);
+        let (bins_right, rms_right) = Self::compute_spectrum(backend, &self.config, right);
+        // Combined level of both channels
+        self.config.window_rms = ((rms_left * rms_left + rms_right * rms_right) / 2.0).sqrt();
+        self.bins = Some(bins_left);
+        self.bins_right = Some(bins_right);
+        self.record_frame();
+        Ok(())
     }
 
-    fn apply_norm(&self, bins: &mut Vec<f32>) {
-        for p in bins.iter_mut() {
-            *p = p.sqrt();
+    fn check_length(&self, samples: &[f32]) -> Result<(), ConfigError> {
+        let needed = self.config.history_size();
+        if samples.len() < needed {
+            return Err(ConfigError::NotEnoughSamples {
+                needed,
+                got: samples.len(),
+            });
         }
-        let peak = bins.iter().fold(0.0_f32, |m, v| m.max(*v)).max(1e-12);
-        let window_rms = self.config.window_rms;
-        let loudness = if window_rms <= self.config.rms_floor {
-            0.0
+        Ok(())
+    }
+
+    /// Append mono samples to the analysis history. Once `history_size()`
+    /// samples have been collected, a spectrum frame is computed every
+    /// `hop_size` samples, however the input is split into calls. Returns
+    /// the number of frames.
+    pub fn push_samples(&mut self, samples: &[f32]) -> usize {
+        self.push(samples, None)
+    }
+
+    /// Like `push_samples` for separate left and right channels of equal length.
+    pub fn push_stereo_samples(&mut self, left: &[f32], right: &[f32]) -> usize {
+        self.push(left, Some(right))
+    }
+
+    fn push(&mut self, left: &[f32], right: Option<&[f32]>) -> usize {
+        self.sync_backend();
+        let len = right.map_or(left.len(), |right| left.len().min(right.len()));
+        self.scope
+            .push(&left[..len], right.map(|right| &right[..len]));
+        // Start over when switching between mono and stereo
+        let expected_right = if right.is_some() {
+            self.history[0].len()
         } else {
-            let norm = (window_rms - self.config.rms_floor)
-                / (self.config.rms_reference - self.config.rms_floor);
-            norm.clamp(0.0, 1.0).powf(self.config.rms_gamma)
+            0
         };
+        if self.history[1].len() != expected_right {
+            self.history.iter_mut().for_each(VecDeque::clear);
+            self.since_hop = 0;
+        }
 
-        let scale = loudness.max(0.001) / peak;
-        for b in bins.iter_mut() {
-            *b = (*b * scale).min(1.0);
+        let history_size = self.config.history_size();
+        let hop = self.config.hop_size();
+        let mut frames = 0;
+        let mut offset = 0;
+        while offset < len {
+            // Stop where the next frame is due, at a hop boundary once the
+            // window is full, so every frame ends exactly on one
+            let to_hop = hop.saturating_sub(self.since_hop);
+            let to_full = history_size.saturating_sub(self.history[0].len());
+            let n = to_hop.max(to_full).max(1).min(len - offset);
+            let range = offset..offset + n;
+            Self::append(&mut self.history[0], &left[range.clone()], history_size);
+            if let Some(right) = right {
+                Self::append(&mut self.history[1], &right[range], history_size);
+            }
+            offset += n;
+            self.since_hop += n;
+
+            if self.since_hop >= hop && self.history[0].len() == history_size {
+                self.since_hop = 0;
+                self.history.iter_mut().for_each(|h| {
+                    h.make_contiguous();
+                });
+                let (left, right) = (self.history[0].as_slices().0, self.history[1].as_slices().0);
+                let backend = self.backend.as_mut();
+                let (bins, rms) = Self::compute_spectrum(backend, &self.config, left);
+                let (bins_right, rms) = if right.is_empty() {
+                    (None, rms)
+                } else {
+                    let (bins_right, rms_right) =
+                        Self::compute_spectrum(backend, &self.config, right);
+                    (
+                        Some(bins_right),
+                        ((rms * rms + rms_right * rms_right) / 2.0).sqrt(),
+                    )
+                };
+                self.bins = Some(bins);
+                self.bins_right = bins_right;
+                self.config.window_rms = rms;
+                self.record_frame();
+                frames += 1;
+            }
         }
+        frames
     }
 
-    fn binned_spectrum(&self, num_bins: usize) -> Vec<f32> {
-        let mut bins = vec![0.0; num_bins];
-
-        let spectrum = match self.spectrum.as_ref() {
-            Some(spectrum) => spectrum,
-            None => return bins,
-        };
+    /// Append `samples` to `history`, keeping the last `history_size`.
+    fn append(history: &mut VecDeque<f32>, samples: &[f32], history_size: usize) {
+        history.extend(samples);
+        if history.len() > history_size {
+            history.drain(..history.len() - history_size);
+        }
+    }
 
-        let layout = self.config.layout.clone();
+    /// True if the last update produced left and right spectra.
+    pub fn has_stereo_spectrum(&self) -> bool {
+        self.bins_right.is_some()
+    }
 
-        for &(freq, mag) in spectrum.data() {
-            let freq_val = freq.val().ln();
-            if freq_val < layout.log_min || freq_val > layout.log_max {
-                continue;
+    /// Add the current spectrum to the spectrogram and advance the
+    /// normalizer by one frame.
+    fn record_frame(&mut self) {
+        let num_bins = self.config.layout.num_bins();
+        let filter_manager = &self.config.filter_manager;
+        let mut frame = self.current_bins(self.bins.as_ref());
+        filter_manager.apply_spatial_filters(&mut frame);
+        // The magnitudes the display normalizes, both channels in stereo
+        let mut magnitudes = frame.clone();
+        if let Some(right) = &self.bins_right
+            && right.len() == num_bins
+        {
+            let mut right = right.clone();
+            filter_manager.apply_spatial_filters(&mut right);
+            for (left, right) in frame.iter_mut().zip(&right) {
+                *left = (*left + right) / 2.0;
             }
-            let bin_index = f32::floor(
-                (freq_val - layout.log_min) / (layout.log_max - layout.log_min)
-                    * (num_bins - 1) as f32,
-            ) as usize;
+            magnitudes.extend(right);
+        }
+        for m in magnitudes.iter_mut() {
+            *m = m.sqrt();
+        }
+        let levels = self.frame_levels();
+        self.config.normalizer_mut().update(&magnitudes, levels);
+
+        self.spectrogram.set_depth(self.config.spectrogram_depth);
+        self.spectrogram.push(frame);
+    }
 
-            if bin_index < num_bins {
-                bins[bin_index] += mag.val() * mag.val();
+    /// Replace the backend if the configured analysis method changed.
+    fn sync_backend(&mut self) {
+        if self.backend.method() != self.config.analysis {
+            self.backend = self.config.analysis.backend();
+        }
+    }
+
+    /// Bin powers of the first `history_size()` samples and RMS level of the
+    /// main window at their end.
+    fn compute_spectrum(
+        backend: &mut dyn SpectrumBackend,
+        config: &VisualizerConfig,
+        samples: &[f32],
+    ) -> (Vec<f32>, f32) {
+        let samples = &samples[..config.history_size()];
+        // Level of the main window, the latest samples
+        let window = &samples[samples.len() - config.window_size..];
+        let rms = (window.iter().map(|x| x * x).sum::<f32>() / window.len() as f32).sqrt();
+        (backend.analyze(samples, config), rms)
+    }
+
+    /// Map bin powers to bar heights as set by `config.output`.
+    fn apply_output(&self, bins: &mut [f32]) {
+        match self.config.output {
+            OutputMode::Relative => self.apply_norm(bins),
+            OutputMode::Decibels {
+                floor_db,
+                ceiling_db,
+            } => {
+                let full_scale = self.backend.full_scale_power(&self.config);
+                for b in bins.iter_mut() {
+                    *b = db_level(*b, full_scale, floor_db, ceiling_db);
+                }
             }
         }
+    }
 
-        bins
+    fn apply_norm(&self, bins: &mut [f32]) {
+        for p in bins.iter_mut() {
+            *p = p.sqrt();
+        }
+        if let Ok(normalizer) = self.config.normalizer.lock() {
+            normalizer.normalize(bins, self.frame_levels());
+        }
+        for b in bins.iter_mut() {
+            *b = b.clamp(0.0, 1.0);
+        }
+    }
+
+    fn frame_levels(&self) -> FrameLevels {
+        FrameLevels {
+            window_rms: self.config.window_rms,
+            full_scale: self.backend.full_scale_power(&self.config).sqrt(),
+        }
+    }
+
+    /// Bins of the last frame, or silence if there is none for the current
+    /// layout.
+    fn current_bins(&self, bins: Option<&Vec<f32>>) -> Vec<f32> {
+        let num_bins = self.config.layout.num_bins();
+        match bins {
+            Some(bins) if bins.len() == num_bins => bins.clone(),
+            _ => vec![0.0; num_bins],
+        }
     }
 
     pub fn visualization_data(&self) -> Vec<f32> {
-        let mut bins = self.binned_spectrum(self.config.num_bins);
+        let mut bins = self.current_bins(self.bins.as_ref());
         let filter_manager = &self.config.filter_manager;
         filter_manager.apply_spatial_filters(&mut bins);
         filter_manager.apply_temporal_filters(&mut bins);
-        self.apply_norm(&mut bins);
+        self.apply_output(&mut bins);
 
         bins
     }
+
+    /// Left and right channel bins, or `None` when no stereo spectrum is available.
+    /// Both channels run through the configured filters with separate temporal
+    /// state and share one normalization, so level differences stay visible.
+    pub fn stereo_visualization_data(&self) -> Option<(Vec<f32>, Vec<f32>)> {
+        let mut left = self.current_bins(self.bins.as_ref());
+        let mut right = self.current_bins(Some(self.bins_right.as_ref()?));
+        let num_bins = left.len();
+
+        let filter_manager = &self.config.filter_manager;
+        filter_manager.apply_spatial_filters(&mut left);
+        filter_manager.apply_spatial_filters(&mut right);
+
+        filter_manager.apply_temporal_filters(&mut left);
+        filter_manager.apply_right_temporal_filters(&mut right);
+
+        // One buffer, so both channels share the normalization
+        left.append(&mut right);
+        self.apply_output(&mut left);
+
+        let right = left.split_off(num_bins);
+        Some((left, right))
+    }
+
+    /// Frames added since `cursor` as levels from 0 to 1 on the dB range of
+    /// the output mode, or `SPECTROGRAM_RANGE_DB` in relative mode. Without a
+    /// cursor, or if the history or the level mapping changed since, the
+    /// update resets to all frames. Frames of an earlier layout are left out.
+    pub fn spectrogram_since(&self, cursor: Option<SpectrogramCursor>) -> SpectrogramUpdate {
+        let (floor_db, ceiling_db) = match self.config.output {
+            OutputMode::Relative => SPECTROGRAM_RANGE_DB,
+            OutputMode::Decibels {
+                floor_db,
+                ceiling_db,
+            } => (floor_db, ceiling_db),
+        };
+        let full_scale = self.backend.full_scale_power(&self.config);
+        let num_bins = self.config.layout.num_bins();
+        let next = self.spectrogram.cursor((floor_db, ceiling_db, full_scale));
+        let since = cursor
+            .filter(|cursor| cursor.mapping == next.mapping)
+            .and_then(|cursor| self.spectrogram.frames_since(cursor));
+        let reset = since.is_none();
+        let frames: Vec<&Vec<f32>> = match since {
+            Some(frames) => frames.collect(),
+            None => self.spectrogram.frames().collect(),
+        };
+        SpectrogramUpdate {
+            reset,
+            frames: frames
+                .into_iter()
+                .filter(|frame| frame.len() == num_bins)
+                .map(|frame| {
+                    frame
+                        .iter()
+                        .map(|&p| db_level(p, full_scale, floor_db, ceiling_db))
+                        .collect()
+                })
+                .collect(),
+            depth: self.spectrogram.depth(),
+            cursor: next,
+        }
+    }
+
+    /// Oscilloscope trace of the latest `seconds` of pushed samples that
+    /// starts at `trigger`.
+    pub fn scope_trace(&self, trigger: Trigger, seconds: f32) -> ScopeTrace {
+        let span = (seconds * self.config.sample_rate as f32).round() as usize;
+        self.scope.trace(trigger, span, self.config.sample_rate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize) -> Vec<f32> {
+        (0..len).map(|i| (i as f32 * 0.05).sin()).collect()
+    }
+
+    /// ⌊(n − window) / hop⌋ + 1 frames once a full window is available.
+    fn expected_frames(n: usize, window_size: usize, hop: usize) -> usize {
+        if n < window_size {
+            0
+        } else {
+            (n - window_size) / hop + 1
+        }
+    }
+
+    #[test]
+    fn push_frame_count_ignores_chunk_sizes() {
+        let samples = tone(20000);
+        for (window_size, overlap) in [(2048, 0.75), (1000, 0.5), (512, 0.0), (4096, 0.9)] {
+            for chunk in [1, 7, 256, 511, 4097, 20000] {
+                let mut visualizer = Visualizer::new(48000, window_size, 50);
+                visualizer.config.set_overlap(overlap).unwrap();
+                let hop = visualizer.config.hop_size();
+                let frames: usize = samples
+                    .chunks(chunk)
+                    .map(|chunk| visualizer.push_samples(chunk))
+                    .sum();
+                assert_eq!(
+                    frames,
+                    expected_frames(samples.len(), window_size, hop),
+                    "window {window_size}, hop {hop}, chunks of {chunk}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stereo_push_frame_count_ignores_chunk_sizes() {
+        let samples = tone(10000);
+        for chunk in [3, 300, 10000] {
+            let mut visualizer = Visualizer::new(48000, 2048, 50);
+            let hop = visualizer.config.hop_size();
+            let frames: usize = samples
+                .chunks(chunk)
+                .map(|chunk| visualizer.push_stereo_samples(chunk, chunk))
+                .sum();
+            assert_eq!(frames, expected_frames(samples.len(), 2048, hop));
+            assert!(visualizer.has_stereo_spectrum());
+        }
+    }
+
+    #[test]
+    fn short_input_is_an_error() {
+        let mut visualizer = Visualizer::new(48000, 2048, 50);
+        let short = tone(2047);
+        let full = tone(2048);
+        assert!(matches!(
+            visualizer.update_spectrum(&short),
+            Err(ConfigError::NotEnoughSamples {
+                needed: 2048,
+                got: 2047
+            })
+        ));
+        assert!(matches!(
+            visualizer.update_stereo_spectrum(&full, &short),
+            Err(ConfigError::NotEnoughSamples { got: 2047, .. })
+        ));
+        assert!(matches!(
+            visualizer.update_stereo_spectrum(&[], &full),
+            Err(ConfigError::NotEnoughSamples { got: 0, .. })
+        ));
+        assert!(visualizer.update_spectrum(&full).is_ok());
+        assert!(
+            visualizer
+                .update_stereo_spectrum(&full, &tone(3000))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn set_window_bounds() {
+        let config = &mut Visualizer::new(48000, 2048, 50).config;
+        assert!(matches!(
+            config.set_window(MIN_WINDOW_SIZE - 1, 1),
+            Err(ConfigError::WindowSize { .. })
+        ));
+        assert!(matches!(
+            config.set_window(MAX_FFT_SIZE / 2 + 1, 2),
+            Err(ConfigError::WindowSize { .. })
+        ));
+        assert!(matches!(
+            config.set_window(1024, 3),
+            Err(ConfigError::ZeroPadding(3))
+        ));
+        assert!(matches!(
+            config.set_window(1024, 0),
+            Err(ConfigError::ZeroPadding(0))
+        ));
+        // Rejected values leave the window as it was
+        assert_eq!((config.window_size(), config.zero_padding()), (2048, 1));
+
+        assert!(config.set_window(MIN_WINDOW_SIZE, 1).is_ok());
+        assert!(config.set_window(MAX_FFT_SIZE, 1).is_ok());
+        assert!(config.set_window(MAX_FFT_SIZE / 4, 4).is_ok());
+        assert_eq!(config.fft_size(), MAX_FFT_SIZE);
+    }
+
+    #[test]
+    fn set_overlap_and_hop_bounds() {
+        let config = &mut Visualizer::new(48000, 1000, 50).config;
+        assert!(matches!(
+            config.set_overlap(-0.1),
+            Err(ConfigError::Overlap(_))
+        ));
+        assert!(matches!(
+            config.set_overlap(MAX_OVERLAP + 0.01),
+            Err(ConfigError::Overlap(_))
+        ));
+        assert!(matches!(
+            config.set_overlap(f32::NAN),
+            Err(ConfigError::Overlap(_))
+        ));
+        assert!(config.set_overlap(0.0).is_ok());
+        assert_eq!(config.hop_size(), 1000);
+        assert!(config.set_overlap(MAX_OVERLAP).is_ok());
+        assert_eq!(config.hop_size(), 50);
+
+        assert!(matches!(
+            config.set_hop_size(0),
+            Err(ConfigError::HopSize(0))
+        ));
+        assert!(matches!(
+            config.set_hop_size(1001),
+            Err(ConfigError::HopSize(1001))
+        ));
+        // Below the hop of the largest overlap
+        assert!(matches!(
+            config.set_hop_size(10),
+            Err(ConfigError::HopSize(10))
+        ));
+        assert!(config.set_hop_size(1000).is_ok());
+        assert!(config.set_hop_size(250).is_ok());
+        assert_eq!(config.hop_size(), 250);
+    }
+
+    #[test]
+    fn padded_non_power_of_two_window() {
+        let mut visualizer = Visualizer::new(48000, 3000, 50);
+        visualizer.config.set_zero_padding(4).unwrap();
+        assert_eq!(visualizer.config.fft_size(), 4096 * 4);
+        visualizer.update_spectrum(&tone(3000)).unwrap();
+        let bins = visualizer.visualization_data();
+        assert_eq!(bins.len(), visualizer.config.layout.num_bins());
+        assert!(bins.iter().all(|b| b.is_finite()));
+        assert!(bins.iter().any(|&b| b > 0.0));
+        assert_eq!(visualizer.push_samples(&tone(3000 + 750 * 2)), 3);
+    }
+
+    #[test]
+    fn padding_change_resets_temporal_state() {
+        let mut visualizer = Visualizer::new(48000, 2048, 50);
+        visualizer.update_spectrum(&tone(2048)).unwrap();
+        visualizer.visualization_data();
+        let filtered = |visualizer: &Visualizer| {
+            visualizer
+                .config
+                .filter_manager
+                .temporal_states()
+                .iter()
+                .any(|(_, state)| state.iter().any(|&v| v != 0.0))
+        };
+        assert!(filtered(&visualizer));
+        visualizer.config.set_zero_padding(2).unwrap();
+        assert!(!filtered(&visualizer));
+    }
+
+    #[test]
+    fn multi_resolution_keeps_the_bass_window() {
+        let mut visualizer = Visualizer::new(48000, 2048, 50);
+        let analysis = AnalysisMethod::options()[2];
+        assert!(matches!(
+            analysis,
+            AnalysisMethod::MultiResolution {
+                bass_window: 8192,
+                ..
+            }
+        ));
+        visualizer.config.set_analysis(analysis).unwrap();
+        assert_eq!(visualizer.config.history_size(), 8192);
+        // The longer window resolves bass below the main window's limit
+        assert_eq!(visualizer.config.layout.edges[0], 20.0);
+        assert!(matches!(
+            visualizer.update_spectrum(&tone(2048)),
+            Err(ConfigError::NotEnoughSamples { needed: 8192, .. })
+        ));
+        let hop = visualizer.config.hop_size();
+        assert_eq!(hop, 512);
+        assert_eq!(
+            visualizer.push_samples(&tone(20000)),
+            expected_frames(20000, 8192, hop)
+        );
+    }
+
+    #[test]
+    fn normalizer_advances_per_analysis_frame() {
+        for normalizer in [
+            NormalizerPreset::Agc {
+                target: 0.8,
+                attack: 0.5,
+                release: 0.9,
+                max_gain_db: 40.0,
+            },
+            NormalizerPreset::Percentile {
+                percentile: 0.9,
+                frames: 4,
+            },
+        ] {
+            let mut visualizer = Visualizer::new(48000, 1024, 50);
+            visualizer.config.set_normalizer(normalizer.build());
+            visualizer.config.filter_manager.clear_temporal_filters();
+            let quiet: Vec<f32> = tone(1024).iter().map(|s| s * 0.01).collect();
+            visualizer.update_spectrum(&tone(1024)).unwrap();
+            visualizer.update_spectrum(&quiet).unwrap();
+            // Drawing the same frame again leaves the result unchanged
+            let first = visualizer.visualization_data();
+            for _ in 0..10 {
+                assert_eq!(visualizer.visualization_data(), first, "{normalizer:?}");
+            }
+            // The quiet frame follows the loud one, so its gain is still low
+            assert!(first.iter().all(|&b| b < 0.5), "{normalizer:?}");
+        }
+    }
 }
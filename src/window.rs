@@ -0,0 +1,122 @@
+use std::f32::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+/// Taper applied to each analysis window before the FFT.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum WindowFunction {
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+    /// 4-term Blackman-Harris, for low sidelobe leakage.
+    BlackmanHarris,
+    /// Flat-top, for accurate amplitudes of tones between FFT bins.
+    FlatTop,
+    /// Kaiser window; larger `beta` trades resolution for lower sidelobes.
+    Kaiser {
+        beta: f32,
+    },
+}
+
+impl WindowFunction {
+    /// One of each kind, with a default Kaiser beta.
+    pub fn options() -> [WindowFunction; 6] {
+        [
+            WindowFunction::Hann,
+            WindowFunction::Hamming,
+            WindowFunction::Blackman,
+            WindowFunction::BlackmanHarris,
+            WindowFunction::FlatTop,
+            WindowFunction::Kaiser { beta: 8.6 },
+        ]
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WindowFunction::Hann => "Hann",
+            WindowFunction::Hamming => "Hamming",
+            WindowFunction::Blackman => "Blackman",
+            WindowFunction::BlackmanHarris => "Blackman-Harris",
+            WindowFunction::FlatTop => "Flat-top",
+            WindowFunction::Kaiser { .. } => "Kaiser",
+        }
+    }
+
+    /// True if both are the same function, ignoring parameters.
+    pub fn same_kind(&self, other: &WindowFunction) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Periodic window of `len` samples.
+    pub fn coefficients(&self, len: usize) -> Vec<f32> {
+        let n = len as f32;
+        match *self {
+            WindowFunction::Hann => cosine_sum(len, &[0.5, 0.5]),
+            WindowFunction::Hamming => cosine_sum(len, &[0.54, 0.46]),
+            WindowFunction::Blackman => cosine_sum(len, &[0.42, 0.5, 0.08]),
+            WindowFunction::BlackmanHarris => {
+                cosine_sum(len, &[0.35875, 0.48829, 0.14128, 0.01168])
+            }
+            WindowFunction::FlatTop => cosine_sum(
+                len,
+                &[0.21557895, 0.41663158, 0.27726316, 0.08357895, 0.006947368],
+            ),
+            WindowFunction::Kaiser { beta } => {
+                let norm = bessel_i0(beta);
+                (0..len)
+                    .map(|i| {
+                        let x = 2.0 * i as f32 / n - 1.0;
+                        bessel_i0(beta * (1.0 - x * x).max(0.0).sqrt()) / norm
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Window coefficients scaled to a coherent gain of one, so a sine keeps
+    /// its peak magnitude whichever window is used.
+    pub fn compensated_coefficients(&self, len: usize) -> Vec<f32> {
+        let mut coefficients = self.coefficients(len);
+        let gain = coefficients.iter().sum::<f32>() / len.max(1) as f32;
+        if gain > 0.0 {
+            for c in coefficients.iter_mut() {
+                *c /= gain;
+            }
+        }
+        coefficients
+    }
+}
+
+/// w[n] = a0 - a1 cos(2πn/N) + a2 cos(4πn/N) - ...
+fn cosine_sum(len: usize, terms: &[f32]) -> Vec<f32> {
+    let n = len as f32;
+    (0..len)
+        .map(|i| {
+            terms
+                .iter()
+                .enumerate()
+                .map(|(k, a)| {
+                    let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * a * (2.0 * PI * k as f32 * i as f32 / n).cos()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Zeroth-order modified Bessel function of the first kind, by its power series.
+fn bessel_i0(x: f32) -> f32 {
+    let half = x / 2.0;
+    let mut term = 1.0_f32;
+    let mut sum = 1.0_f32;
+    for k in 1..50 {
+        term *= (half / k as f32) * (half / k as f32);
+        sum += term;
+        if term < sum * 1e-8 {
+            break;
+        }
+    }
+    sum
+}